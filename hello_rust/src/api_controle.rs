@@ -0,0 +1,176 @@
+// api_controle.rs
+use std::sync::Arc;
+use std::time::Duration;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use crate::simulacao::Simulacao;
+
+// ---------------- Protocolo ----------------
+//
+// Uma requisição JSON por linha, uma resposta JSON por linha. Exemplos:
+//   {"comando": "estado"}                       {"comando": "estado", "hospital": 1}
+//   {"comando": "logs", "ultimos": 20}
+//   {"comando": "injetar", "nome": "P20", "idade": 40, "condicao": "Fratura", "cirurgia": true}
+//   {"comando": "injetar", "nome": "P21", "idade": 70, "condicao": "Dor", "prazo": 30}
+//   {"comando": "pausar"}                       {"comando": "retomar"}
+//   {"comando": "capacidade", "recurso": "sala", "quantidade": 3}
+//   {"comando": "preemptar", "paciente": "P03-Normal"}
+// Respostas trazem "ok": true e os dados pedidos, ou "ok": false e "erro".
+
+/// Comandos aceitos pela API de controle. `hospital` é o índice na rede (0 = principal).
+#[derive(Debug, Deserialize)]
+#[serde(tag = "comando", rename_all = "snake_case")]
+enum Comando {
+    /// Estado de um hospital (ou de todos), no formato do `EstadoRecursosGUI`.
+    Estado { hospital: Option<usize> },
+    /// Últimas linhas do log de eventos de um hospital.
+    Logs {
+        #[serde(default)]
+        hospital: usize,
+        ultimos: Option<usize>,
+    },
+    /// Admite um novo paciente com o caminho clínico padrão. Com `prazo` (segundos de tempo
+    /// simulado), o atendimento é cancelado se não terminar a tempo.
+    Injetar {
+        nome: String,
+        idade: u32,
+        condicao: String,
+        #[serde(default)]
+        cirurgia: bool,
+        #[serde(default)]
+        prioridade: u8,
+        #[serde(default)]
+        hospital: usize,
+        prazo: Option<f64>,
+    },
+    Pausar,
+    Retomar,
+    /// Altera a capacidade de um tipo de recurso.
+    Capacidade {
+        #[serde(default)]
+        hospital: usize,
+        recurso: String,
+        quantidade: usize,
+    },
+    /// Preempta um paciente: o atendimento dele é cancelado onde estiver e as reservas que ele
+    /// mantém são devolvidas.
+    Preemptar { paciente: String },
+}
+
+/// Quantidade de linhas de log devolvidas quando `ultimos` não é informado.
+const LOGS_PADRAO: usize = 50;
+
+/// Aceita conexões na `endereco` e atende cada cliente numa tarefa própria.
+pub async fn iniciar_servidor_controle(simulacao: Arc<Simulacao>, endereco: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(endereco).await?;
+    println!("🛰️ API de controle ouvindo em {}", endereco);
+
+    loop {
+        let (stream, cliente) = listener.accept().await?;
+        println!("🛰️ Cliente de controle conectado: {}", cliente);
+        tokio::spawn(atender_cliente(stream, simulacao.clone()));
+    }
+}
+
+async fn atender_cliente(stream: TcpStream, simulacao: Arc<Simulacao>) {
+    let (leitura, mut escrita) = stream.into_split();
+    let mut linhas = BufReader::new(leitura).lines();
+
+    while let Ok(Some(linha)) = linhas.next_line().await {
+        if linha.trim().is_empty() {
+            continue;
+        }
+
+        let resposta = match serde_json::from_str::<Comando>(&linha) {
+            Ok(comando) => executar(comando, &simulacao).await,
+            Err(e) => erro(format!("requisição inválida: {}", e)),
+        };
+
+        let mut saida = resposta.to_string();
+        saida.push('\n');
+        if escrita.write_all(saida.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+fn erro(mensagem: String) -> Value {
+    json!({ "ok": false, "erro": mensagem })
+}
+
+async fn executar(comando: Comando, simulacao: &Simulacao) -> Value {
+    let rede = &simulacao.rede;
+
+    match comando {
+        Comando::Estado { hospital } => {
+            let indices: Vec<usize> = match hospital {
+                Some(i) if i >= rede.hospitais.len() => return erro(format!("hospital {} não existe", i)),
+                Some(i) => vec![i],
+                None => (0..rede.hospitais.len()).collect(),
+            };
+
+            let mut hospitais = vec![];
+            for i in indices {
+                let h = &rede.hospitais[i];
+                let mut estado = h.estado.lock().await;
+                estado.sincronizar(&h.recursos).await;
+                hospitais.push(json!({ "indice": i, "nome": h.nome, "estado": &*estado }));
+            }
+            json!({ "ok": true, "pausado": simulacao.controle.pausado(), "hospitais": hospitais })
+        }
+        Comando::Logs { hospital, ultimos } => {
+            let Some(h) = rede.hospitais.get(hospital) else {
+                return erro(format!("hospital {} não existe", hospital));
+            };
+            let estado = h.estado.lock().await;
+            let ultimos = ultimos.unwrap_or(LOGS_PADRAO).min(estado.logs.len());
+            json!({ "ok": true, "logs": &estado.logs[estado.logs.len() - ultimos..] })
+        }
+        Comando::Injetar { nome, idade, condicao, cirurgia, prioridade, hospital, prazo } => {
+            let mut paciente = Simulacao::paciente_avulso(&nome, idade, &condicao, cirurgia, prioridade);
+            if let Some(prazo) = prazo {
+                match Duration::try_from_secs_f64(prazo) {
+                    Ok(prazo) => paciente = paciente.com_prazo(prazo),
+                    Err(_) => return erro(format!("prazo inválido: {}", prazo)),
+                }
+            }
+            match simulacao.admitir(paciente, hospital) {
+                Ok(()) => json!({ "ok": true, "paciente": nome, "hospital": hospital }),
+                Err(e) => erro(e),
+            }
+        }
+        Comando::Pausar => {
+            simulacao.controle.pausar();
+            json!({ "ok": true, "pausado": true })
+        }
+        Comando::Retomar => {
+            simulacao.controle.retomar();
+            json!({ "ok": true, "pausado": false })
+        }
+        Comando::Capacidade { hospital, recurso, quantidade } => {
+            match rede.alterar_capacidade(hospital, &recurso, quantidade, "API de controle").await {
+                Ok(anterior) => json!({ "ok": true, "recurso": recurso, "anterior": anterior, "capacidade": quantidade }),
+                Err(e) => erro(e),
+            }
+        }
+        Comando::Preemptar { paciente } => {
+            for (i, h) in rede.hospitais.iter().enumerate() {
+                let retrato = h.recursos.retrato_alocacao().await;
+                let aguardando = retrato.pedidos.contains_key(&paciente);
+                if !aguardando && !retrato.alocacao.contains_key(&paciente) {
+                    continue;
+                }
+
+                h.recursos.preempcao_paciente(&paciente, "API de controle").await;
+                h.estado.lock().await.registrar_log(&format!(
+                    "♻️ Paciente {} preemptado pela API de controle",
+                    paciente
+                ));
+                return json!({ "ok": true, "paciente": paciente, "hospital": i, "aguardando": aguardando });
+            }
+            erro(format!("paciente {} não está esperando nem mantendo recursos", paciente))
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// auditoria.rs
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+use crate::monitor_gui::EstadoRecursosGUI;
+use crate::recursos::{Recursos, TipoRecurso};
+
+/// Intervalo entre duas conferências, em tempo simulado.
+const INTERVALO_AUDITORIA: Duration = Duration::from_millis(100);
+
+// ---------------- Invariantes dos Recursos ----------------
+
+/// Invariante de um pool de recursos que deixou de valer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Invariante {
+    /// O paciente ocupa mais de um slot do mesmo tipo.
+    SlotDuplicado { paciente: String, slots: usize },
+    /// Há mais slots ocupados do que permissões tiradas do semáforo.
+    SlotsSemPermissao { ocupados: usize, permissoes: usize },
+    /// O paciente está na fila de um recurso cujo slot ele já ocupa.
+    FilaComSlot { paciente: String },
+    /// Os slots do paciente não batem com os `EventoUso` abertos dele.
+    UsoDescasado { paciente: String, slots: usize, eventos_abertos: usize },
+}
+
+/// Violação encontrada pela auditoria: quando, em qual recurso e qual invariante.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViolacaoInvariante {
+    pub tempo: f64,
+    pub tipo: TipoRecurso,
+    pub invariante: Invariante,
+}
+
+impl fmt::Display for ViolacaoInvariante {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[t={:.1}s] {}: ", self.tempo, self.tipo)?;
+        match &self.invariante {
+            Invariante::SlotDuplicado { paciente, slots } => write!(f, "{} ocupa {} slots", paciente, slots),
+            Invariante::SlotsSemPermissao { ocupados, permissoes } => {
+                write!(f, "{} slots ocupados com {} permissões tiradas do semáforo", ocupados, permissoes)
+            }
+            Invariante::FilaComSlot { paciente } => write!(f, "{} está na fila ocupando um slot", paciente),
+            Invariante::UsoDescasado { paciente, slots, eventos_abertos } => {
+                write!(f, "{} ocupa {} slots e tem {} usos abertos no histórico", paciente, slots, eventos_abertos)
+            }
+        }
+    }
+}
+
+/// Confere as invariantes de todos os pools num instante:
+/// - nenhum paciente ocupa dois slots do mesmo tipo;
+/// - os slots ocupados não passam das permissões tiradas do semáforo;
+/// - a fila de um recurso não tem quem já ocupa um slot dele;
+/// - cada slot ocupado tem exatamente um `EventoUso` aberto, e vice-versa.
+///
+/// Nada é esperado: devolve `None` se algum registro estiver travado (sendo alterado) nesse instante.
+pub fn conferir(recursos: &Recursos) -> Option<Vec<ViolacaoInvariante>> {
+    let tempo = recursos.controle.agora().as_secs_f64();
+    let mut violacoes = vec![];
+    for pool in &recursos.pools {
+        let tipo = pool.config.tipo;
+        let slots_lock = pool.slots.try_lock().ok()?;
+        let fila = pool.fila.try_lock().ok()?;
+        let eventos = recursos.historico_uso.eventos(tipo)?.try_lock().ok()?;
+        let mut violacao = |invariante| violacoes.push(ViolacaoInvariante { tempo, tipo, invariante });
+
+        let ocupados: Vec<&String> = slots_lock.iter().flatten().collect();
+        let abertos: Vec<&String> = eventos.iter().filter(|e| e.fim == 0.0).map(|e| &e.nome_paciente).collect();
+        let mut pacientes: Vec<&String> = ocupados.iter().chain(&abertos).copied().collect();
+        pacientes.sort();
+        pacientes.dedup();
+
+        // Instâncias em retirada ainda contam: a permissão delas só some quando forem devolvidas
+        let permissoes = (pool.capacidade() + pool.reducao_pendente()).saturating_sub(pool.semaforo.available_permits());
+        if ocupados.len() > permissoes {
+            violacao(Invariante::SlotsSemPermissao { ocupados: ocupados.len(), permissoes });
+        }
+
+        for paciente in pacientes {
+            let slots = ocupados.iter().filter(|n| **n == paciente).count();
+            let eventos_abertos = abertos.iter().filter(|n| **n == paciente).count();
+            if slots > 1 {
+                violacao(Invariante::SlotDuplicado { paciente: paciente.clone(), slots });
+            }
+            if slots > 0 && fila.contains(paciente) {
+                violacao(Invariante::FilaComSlot { paciente: paciente.clone() });
+            }
+            if slots != eventos_abertos {
+                violacao(Invariante::UsoDescasado { paciente: paciente.clone(), slots, eventos_abertos });
+            }
+        }
+    }
+    Some(violacoes)
+}
+
+// ---------------- Tarefa de Auditoria ----------------
+
+/// O que a auditoria faz ao encontrar uma violação, além de registrá-la.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReacaoViolacao {
+    /// Registra no log e segue a simulação.
+    #[default]
+    Registrar,
+    /// Registra e pausa a simulação, congelando o estado inconsistente para inspeção.
+    Pausar,
+}
+
+/// Confere as invariantes dos recursos a cada `INTERVALO_AUDITORIA` (roda ao lado de `iniciar_monitor`).
+/// Cada violação é registrada uma vez, quando aparece; se ela sumir e voltar, é registrada de novo.
+pub async fn iniciar_auditoria(
+    recursos: Arc<Recursos>,
+    estado_gui: Arc<Mutex<EstadoRecursosGUI>>,
+    logs: Arc<Mutex<Vec<String>>>,
+    reacao: ReacaoViolacao,
+) {
+    // Violações vistas na última conferência (sem o tempo), para não repetir o registro
+    let mut ativas: Vec<(TipoRecurso, Invariante)> = vec![];
+
+    loop {
+        if let Some(violacoes) = conferir(&recursos) {
+            let novas: Vec<&ViolacaoInvariante> = violacoes
+                .iter()
+                .filter(|v| !ativas.iter().any(|(tipo, invariante)| *tipo == v.tipo && *invariante == v.invariante))
+                .collect();
+
+            if !novas.is_empty() {
+                let mut estado = estado_gui.lock().await;
+                let mut logs_lock = logs.lock().await;
+                for violacao in &novas {
+                    let log_msg = format!("🚨 Invariante violada: {}", violacao);
+                    println!("\n[J.A.R.V.I.S.] {}", log_msg);
+                    estado.registrar_log(&log_msg);
+                    logs_lock.push(log_msg);
+                }
+                if reacao == ReacaoViolacao::Pausar && !recursos.controle.pausado() {
+                    recursos.controle.pausar();
+                    let log_msg = "⏸️ Simulação pausada pela auditoria para inspeção do estado".to_string();
+                    println!("\n[J.A.R.V.I.S.] {}", log_msg);
+                    estado.registrar_log(&log_msg);
+                    logs_lock.push(log_msg);
+                }
+            }
+            ativas = violacoes.into_iter().map(|v| (v.tipo, v.invariante)).collect();
+        }
+
+        recursos.controle.dormir(INTERVALO_AUDITORIA).await;
+    }
+}
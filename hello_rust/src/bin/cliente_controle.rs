@@ -0,0 +1,109 @@
+// cliente_controle.rs
+//
+// Cliente de teste da API de controle da simulação (JSON por linha sobre TCP).
+//
+//   cargo run --bin cliente_controle -- estado [hospital]
+//   cargo run --bin cliente_controle -- logs [ultimos]
+//   cargo run --bin cliente_controle -- pausar | retomar
+//   cargo run --bin cliente_controle -- injetar <nome> <idade> <condicao> [cirurgia] [prioridade] [prazo]
+//   cargo run --bin cliente_controle -- capacidade <recurso> <quantidade> [hospital]
+//   cargo run --bin cliente_controle -- preemptar <paciente>
+//
+// Sem argumentos, lê requisições JSON (uma por linha) da entrada padrão.
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+const ENDERECO_PADRAO: &str = "127.0.0.1:7878";
+
+/// Monta a requisição a partir dos atalhos da linha de comando.
+fn requisicao(args: &[String]) -> Result<Value, String> {
+    let arg = |i: usize| args.get(i).map(String::as_str);
+    let numero = |i: usize, campo: &str| -> Result<Option<u64>, String> {
+        arg(i).map(|v| v.parse::<u64>().map_err(|_| format!("{} inválido: {}", campo, v))).transpose()
+    };
+
+    match arg(0) {
+        Some("estado") => Ok(json!({ "comando": "estado", "hospital": numero(1, "hospital")? })),
+        Some("logs") => Ok(json!({ "comando": "logs", "ultimos": numero(1, "ultimos")? })),
+        Some("pausar") => Ok(json!({ "comando": "pausar" })),
+        Some("retomar") => Ok(json!({ "comando": "retomar" })),
+        Some("injetar") => {
+            let (Some(nome), Some(idade), Some(condicao)) = (arg(1), numero(2, "idade")?, arg(3)) else {
+                return Err("uso: injetar <nome> <idade> <condicao> [cirurgia] [prioridade] [prazo]".into());
+            };
+            Ok(json!({
+                "comando": "injetar",
+                "nome": nome,
+                "idade": idade,
+                "condicao": condicao,
+                "cirurgia": matches!(arg(4), Some("sim" | "true" | "cirurgia")),
+                "prioridade": numero(5, "prioridade")?.unwrap_or(0),
+                "prazo": numero(6, "prazo")?,
+            }))
+        }
+        Some("capacidade") => {
+            let (Some(recurso), Some(quantidade)) = (arg(1), numero(2, "quantidade")?) else {
+                return Err("uso: capacidade <recurso> <quantidade> [hospital]".into());
+            };
+            Ok(json!({
+                "comando": "capacidade",
+                "recurso": recurso,
+                "quantidade": quantidade,
+                "hospital": numero(3, "hospital")?.unwrap_or(0),
+            }))
+        }
+        Some("preemptar") => match arg(1) {
+            Some(paciente) => Ok(json!({ "comando": "preemptar", "paciente": paciente })),
+            None => Err("uso: preemptar <paciente>".into()),
+        },
+        Some(outro) => Err(format!("comando desconhecido: {}", outro)),
+        None => Err("nenhum comando".into()),
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let endereco = std::env::var("SIMULADOR_CONTROLE").unwrap_or_else(|_| ENDERECO_PADRAO.to_string());
+
+    let stream = TcpStream::connect(&endereco).await?;
+    let (leitura, mut escrita) = stream.into_split();
+    let mut respostas = BufReader::new(leitura).lines();
+
+    // Requisições: o atalho da linha de comando ou as linhas da entrada padrão
+    let requisicoes: Vec<String> = if args.is_empty() {
+        let mut entrada = BufReader::new(tokio::io::stdin()).lines();
+        let mut linhas = vec![];
+        while let Some(linha) = entrada.next_line().await? {
+            if !linha.trim().is_empty() {
+                linhas.push(linha);
+            }
+        }
+        linhas
+    } else {
+        match requisicao(&args) {
+            Ok(valor) => vec![valor.to_string()],
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(2);
+            }
+        }
+    };
+
+    for linha in requisicoes {
+        escrita.write_all(format!("{}\n", linha).as_bytes()).await?;
+        match respostas.next_line().await? {
+            Some(resposta) => match serde_json::from_str::<Value>(&resposta) {
+                Ok(valor) => println!("{}", serde_json::to_string_pretty(&valor).unwrap_or(resposta)),
+                Err(_) => println!("{}", resposta),
+            },
+            None => {
+                eprintln!("❌ Conexão encerrada pelo simulador");
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// cancelamento.rs
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Por que o atendimento de um paciente foi cancelado.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MotivoCancelamento {
+    /// O operador interrompeu o atendimento (GUI).
+    Operador,
+    /// Preempção pedida pelo monitor de deadlock ou pela API de controle (`origem`).
+    Preempcao { origem: String },
+    /// O atendimento passou do prazo máximo do paciente (tempo simulado).
+    PrazoEsgotado(Duration),
+}
+
+impl fmt::Display for MotivoCancelamento {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MotivoCancelamento::Operador => write!(f, "interrompido pelo operador"),
+            MotivoCancelamento::Preempcao { origem } => write!(f, "preemptado ({})", origem),
+            MotivoCancelamento::PrazoEsgotado(prazo) => write!(f, "prazo de {:.1}s esgotado", prazo.as_secs_f64()),
+        }
+    }
+}
+
+/// Alça de cancelamento do atendimento de um paciente. As cópias compartilham o mesmo estado:
+/// qualquer uma cancela, e só o primeiro motivo vale.
+#[derive(Clone)]
+pub struct Cancelamento {
+    motivo: Arc<watch::Sender<Option<MotivoCancelamento>>>,
+}
+
+impl Cancelamento {
+    pub fn novo() -> Self {
+        Self { motivo: Arc::new(watch::Sender::new(None)) }
+    }
+
+    /// Cancela com `motivo`; retorna `false` se já estava cancelado (o primeiro motivo é mantido).
+    pub fn cancelar(&self, motivo: MotivoCancelamento) -> bool {
+        self.motivo.send_if_modified(|atual| {
+            if atual.is_some() {
+                return false;
+            }
+            *atual = Some(motivo);
+            true
+        })
+    }
+
+    /// Espera o cancelamento e retorna o motivo (na hora, se já foi cancelado).
+    pub async fn cancelado(&self) -> MotivoCancelamento {
+        let mut receptor = self.motivo.subscribe();
+        let motivo = receptor.wait_for(Option::is_some).await.expect("o próprio Cancelamento mantém o emissor");
+        motivo.clone().expect("esperado até haver motivo")
+    }
+
+    /// Roda `futuro` até o fim ou até o cancelamento, o que vier antes. Cancelado, o futuro é
+    /// descartado no ponto de espera em que estava (as reservas que ele mantinha são devolvidas).
+    pub async fn executar<F: Future>(&self, futuro: F) -> Result<F::Output, MotivoCancelamento> {
+        tokio::select! {
+            biased;
+            motivo = self.cancelado() => Err(motivo),
+            saida = futuro => Ok(saida),
+        }
+    }
+}
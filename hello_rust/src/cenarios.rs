@@ -0,0 +1,192 @@
+// cenarios.rs
+use std::fmt;
+use std::str::FromStr;
+use crate::paciente::{Etapa, ModoAquisicao, Paciente, Trajeto};
+use crate::recursos::{ConfigRecurso, PoliticaOrdem, Recursos, TipoRecurso, MEDICO, SALA, LEITO, ENFERMEIRO};
+
+// ---------------- Estratégias contra Deadlock ----------------
+
+/// Estratégia aplicada a um cenário. A detecção do monitor fica sempre ligada;
+/// as demais estratégias impedem que o ciclo chegue a se formar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Estrategia {
+    /// Nada é prevenido: o monitor detecta a espera circular e preempta uma vítima.
+    #[default]
+    Deteccao,
+    /// Prevenção por ordem global: pedidos fora da ordem dos recursos são rejeitados.
+    Ordem,
+    /// Prevenção por aquisição atômica: cada etapa devolve o que mantém e pede tudo de uma vez.
+    Atomica,
+    /// Evitação pelo algoritmo do banqueiro: só são concedidos pedidos que mantêm o estado seguro.
+    Banqueiro,
+}
+
+impl Estrategia {
+    /// Aplica a estratégia à configuração dos recursos.
+    pub fn configurar(self, recursos: Recursos) -> Recursos {
+        match self {
+            Estrategia::Deteccao | Estrategia::Atomica => recursos,
+            Estrategia::Ordem => recursos.com_politica_ordem(PoliticaOrdem::Rejeitar),
+            Estrategia::Banqueiro => recursos.com_algoritmo_banqueiro(),
+        }
+    }
+
+    /// Modo de aquisição dos trajetos (e da equipe cirúrgica) sob esta estratégia.
+    pub fn modo(self) -> ModoAquisicao {
+        match self {
+            Estrategia::Atomica => ModoAquisicao::Atomica,
+            _ => ModoAquisicao::Incremental,
+        }
+    }
+}
+
+impl fmt::Display for Estrategia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Estrategia::Deteccao => write!(f, "detecção e recuperação"),
+            Estrategia::Ordem => write!(f, "prevenção por ordem global"),
+            Estrategia::Atomica => write!(f, "prevenção por aquisição atômica"),
+            Estrategia::Banqueiro => write!(f, "evitação pelo algoritmo do banqueiro"),
+        }
+    }
+}
+
+impl FromStr for Estrategia {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "deteccao" => Ok(Estrategia::Deteccao),
+            "ordem" => Ok(Estrategia::Ordem),
+            "atomica" => Ok(Estrategia::Atomica),
+            "banqueiro" => Ok(Estrategia::Banqueiro),
+            _ => Err(format!("estratégia '{}' desconhecida (use deteccao, ordem, atomica ou banqueiro)", s)),
+        }
+    }
+}
+
+// ---------------- Catálogo de Cenários ----------------
+
+/// Cenário reprodutível: recursos próprios e pacientes com trajetos e chegadas fixos.
+pub struct Cenario {
+    pub nome: &'static str,
+    pub descricao: &'static str,
+    pub estrategia: Estrategia,
+    pub recursos: Vec<ConfigRecurso>,
+    pub pacientes: Vec<Paciente>,
+}
+
+/// Nome e descrição dos cenários disponíveis (`cargo run -- <cenario> [estrategia]`).
+pub const CATALOGO: &[(&str, &str)] = &[
+    ("leito_medico", "Um paciente pega o leito e depois pede o médico; outro faz o contrário."),
+    ("filosofos", "Cinco cirurgiões em volta de cinco bandejas de instrumental (jantar dos filósofos)."),
+    ("ciclo_cirurgico", "Três pacientes disputam médico, sala e enfermeiro em ordens que fecham um ciclo."),
+];
+
+/// Monta um cenário do catálogo com a estratégia escolhida.
+pub fn montar(nome: &str, estrategia: Estrategia) -> Option<Cenario> {
+    let (nome, descricao) = CATALOGO.iter().copied().find(|(n, _)| *n == nome)?;
+    let (recursos, trajetos) = match nome {
+        "leito_medico" => leito_medico(),
+        "filosofos" => filosofos(),
+        "ciclo_cirurgico" => ciclo_cirurgico(),
+        _ => return None,
+    };
+
+    let pacientes = trajetos
+        .into_iter()
+        .map(|(paciente, trajeto)| paciente.com_trajeto(trajeto.com_modo(estrategia.modo())))
+        .collect();
+
+    Some(Cenario { nome, descricao, estrategia, recursos, pacientes })
+}
+
+/// Recurso de capacidade unitária: com uma instância só, a espera circular é garantida.
+const fn unitario(tipo: TipoRecurso, nome: &'static str, instancia: &'static str, icone: &'static str, ordem: u32) -> ConfigRecurso {
+    ConfigRecurso { tipo, nome, instancia, icone, capacidade: 1, ordem }
+}
+
+type Trajetos = Vec<(Paciente, Trajeto)>;
+
+fn leito_medico() -> (Vec<ConfigRecurso>, Trajetos) {
+    let recursos = vec![
+        unitario(MEDICO, "Médicos", "Médico", "👨‍⚕️", 1),
+        unitario(LEITO, "Leitos", "Leito", "🛏️", 2),
+    ];
+    let trajetos = vec![
+        (
+            Paciente::novo("A-LeitoPrimeiro", 40, "Fratura", false, 0),
+            Trajeto::novo(0.0, vec![
+                Etapa::nova("Internação", &[LEITO], 1.0),
+                Etapa::nova("Avaliação no leito", &[MEDICO], 2.0).liberando(&[MEDICO]),
+                Etapa::nova("Repouso", &[], 1.0),
+            ]),
+        ),
+        (
+            Paciente::novo("B-MedicoPrimeiro", 55, "Apendicite", false, 0),
+            Trajeto::novo(0.0, vec![
+                Etapa::nova("Consulta", &[MEDICO], 1.0),
+                Etapa::nova("Encaminhamento ao leito", &[LEITO], 1.0).liberando(&[MEDICO]),
+                Etapa::nova("Observação", &[], 2.0),
+            ]),
+        ),
+        // Só precisa do médico, mas fica preso atrás do ciclo
+        (
+            Paciente::novo("C-Consulta", 30, "Dor", false, 0),
+            Trajeto::novo(2.0, vec![Etapa::nova("Consulta", &[MEDICO], 1.0)]),
+        ),
+    ];
+    (recursos, trajetos)
+}
+
+const BANDEJAS: [TipoRecurso; 5] = ["bandeja_1", "bandeja_2", "bandeja_3", "bandeja_4", "bandeja_5"];
+const NOMES_BANDEJAS: [&str; 5] = ["Bandeja 1", "Bandeja 2", "Bandeja 3", "Bandeja 4", "Bandeja 5"];
+const CIRURGIOES: [&str; 5] = ["Cirurgiao-1", "Cirurgiao-2", "Cirurgiao-3", "Cirurgiao-4", "Cirurgiao-5"];
+
+fn filosofos() -> (Vec<ConfigRecurso>, Trajetos) {
+    let recursos = (0..BANDEJAS.len())
+        .map(|i| unitario(BANDEJAS[i], NOMES_BANDEJAS[i], "Kit", "🧰", i as u32 + 1))
+        .collect();
+
+    // Cada cirurgião pega a bandeja à esquerda, prepara e depois pede a da direita
+    let trajetos = (0..BANDEJAS.len())
+        .map(|i| {
+            let esquerda = BANDEJAS[i];
+            let direita = BANDEJAS[(i + 1) % BANDEJAS.len()];
+            (
+                Paciente::novo(CIRURGIOES[i], 50, "Apendicite", true, 0),
+                Trajeto::novo(0.0, vec![
+                    Etapa::nova("Preparo", &[esquerda], 1.0),
+                    Etapa::nova("Cirurgia", &[direita], 3.0),
+                ]),
+            )
+        })
+        .collect();
+    (recursos, trajetos)
+}
+
+fn ciclo_cirurgico() -> (Vec<ConfigRecurso>, Trajetos) {
+    let recursos = vec![
+        unitario(MEDICO, "Médicos", "Médico", "👨‍⚕️", 1),
+        unitario(SALA, "Salas Cirurgia", "Sala", "🏥", 2),
+        unitario(ENFERMEIRO, "Enfermeiros", "Enfermeiro", "👩‍⚕️", 3),
+    ];
+    let ciclo = [
+        ("P1-SalaEnfermeiro", "Politraum", SALA, ENFERMEIRO),
+        ("P2-EnfermeiroMedico", "Infarto", ENFERMEIRO, MEDICO),
+        ("P3-MedicoSala", "Apendicite", MEDICO, SALA),
+    ];
+    let trajetos = ciclo
+        .into_iter()
+        .map(|(nome, condicao, primeiro, segundo)| {
+            (
+                Paciente::novo(nome, 45, condicao, true, 1),
+                Trajeto::novo(0.0, vec![
+                    Etapa::nova("Preparo", &[primeiro], 1.0),
+                    Etapa::nova("Cirurgia", &[segundo], 2.0),
+                ]),
+            )
+        })
+        .collect();
+    (recursos, trajetos)
+}
@@ -0,0 +1,157 @@
+// clinica.rs
+use rand::Rng;
+use std::fmt;
+use std::time::Duration;
+
+// ---------------- Desfecho Clínico ----------------
+
+/// Desfecho clínico de um paciente tratado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Desfecho {
+    Bom,
+    Complicacao,
+    Obito,
+}
+
+impl fmt::Display for Desfecho {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Desfecho::Bom => write!(f, "Bom"),
+            Desfecho::Complicacao => write!(f, "Complicação"),
+            Desfecho::Obito => write!(f, "Óbito"),
+        }
+    }
+}
+
+/// Modelo de desfecho: as probabilidades de complicação e óbito partem de um valor base
+/// e crescem linearmente com o atraso (em unidades de tempo) além do prazo clínico.
+#[derive(Debug, Clone, Copy)]
+pub struct ModeloDesfecho {
+    pub complicacao_base: f64,
+    pub obito_base: f64,
+    pub complicacao_por_unidade: f64,
+    pub obito_por_unidade: f64,
+}
+
+impl ModeloDesfecho {
+    /// Retorna as probabilidades (bom, complicação, óbito) para um dado atraso.
+    pub fn probabilidades(&self, atraso: f64) -> (f64, f64, f64) {
+        let atraso = atraso.max(0.0);
+        let obito = (self.obito_base + self.obito_por_unidade * atraso).clamp(0.0, 0.95);
+        let complicacao = (self.complicacao_base + self.complicacao_por_unidade * atraso).clamp(0.0, 1.0 - obito);
+        (1.0 - obito - complicacao, complicacao, obito)
+    }
+
+    /// Sorteia um desfecho de acordo com as probabilidades do atraso informado.
+    pub fn sortear<R: Rng + ?Sized>(&self, atraso: f64, rng: &mut R) -> Desfecho {
+        let (_, complicacao, obito) = self.probabilidades(atraso);
+        let sorteio: f64 = rng.random();
+        if sorteio < obito {
+            Desfecho::Obito
+        } else if sorteio < obito + complicacao {
+            Desfecho::Complicacao
+        } else {
+            Desfecho::Bom
+        }
+    }
+}
+
+// ---------------- Perfis Clínicos por Condição ----------------
+
+/// Perfil clínico de uma condição: prazo porta-tratamento e modelo de desfecho.
+/// O prazo é medido em unidades de tempo da simulação (as mesmas das etapas,
+/// que são multiplicadas por `ESCALA_TEMPO`).
+#[derive(Debug, Clone, Copy)]
+pub struct PerfilClinico {
+    pub condicao: &'static str,
+    pub prazo: f64,
+    pub modelo: ModeloDesfecho,
+}
+
+const fn perfil(
+    condicao: &'static str,
+    prazo: f64,
+    complicacao_base: f64,
+    obito_base: f64,
+    complicacao_por_unidade: f64,
+    obito_por_unidade: f64,
+) -> PerfilClinico {
+    PerfilClinico {
+        condicao,
+        prazo,
+        modelo: ModeloDesfecho {
+            complicacao_base,
+            obito_base,
+            complicacao_por_unidade,
+            obito_por_unidade,
+        },
+    }
+}
+
+/// Catálogo de condições conhecidas. Condições tempo-dependentes (Infarto, AVC,
+/// Politrauma) têm prazos curtos e risco que cresce rapidamente com o atraso.
+const PERFIS: &[PerfilClinico] = &[
+    perfil("Infarto", 8.0, 0.05, 0.02, 0.08, 0.04),
+    perfil("AVC", 7.0, 0.08, 0.03, 0.10, 0.03),
+    perfil("Politraum", 9.0, 0.10, 0.05, 0.06, 0.04),
+    perfil("Apendicite", 20.0, 0.03, 0.005, 0.02, 0.005),
+    perfil("Fratura", 30.0, 0.02, 0.0, 0.01, 0.0),
+    perfil("Gripe Forte", 40.0, 0.01, 0.0, 0.005, 0.001),
+    perfil("Corte", 30.0, 0.01, 0.0, 0.005, 0.0),
+    perfil("Dor", 40.0, 0.01, 0.0, 0.005, 0.0),
+];
+
+/// Perfil usado para condições fora do catálogo: prazo folgado e risco baixo.
+const PERFIL_PADRAO: PerfilClinico = perfil("Outra", 40.0, 0.01, 0.0, 0.005, 0.0);
+
+/// Condições do catálogo (usadas nos formulários e nos pacientes sorteados).
+pub fn condicoes() -> impl Iterator<Item = &'static str> {
+    PERFIS.iter().map(|p| p.condicao)
+}
+
+/// Busca o perfil clínico de uma condição (ou o perfil padrão se não catalogada).
+pub fn perfil_clinico(condicao: &str) -> &'static PerfilClinico {
+    PERFIS
+        .iter()
+        .find(|p| p.condicao.eq_ignore_ascii_case(condicao))
+        .unwrap_or(&PERFIL_PADRAO)
+}
+
+/// Registro do desfecho de um paciente tratado.
+#[derive(Debug, Clone)]
+pub struct RegistroDesfecho {
+    pub condicao: String,
+    /// Tempo porta-tratamento, em unidades de tempo da simulação.
+    pub porta_tratamento: f64,
+    pub prazo: f64,
+    pub desfecho: Desfecho,
+}
+
+impl RegistroDesfecho {
+    pub fn atraso(&self) -> f64 {
+        (self.porta_tratamento - self.prazo).max(0.0)
+    }
+
+    pub fn dentro_do_prazo(&self) -> bool {
+        self.porta_tratamento <= self.prazo
+    }
+}
+
+/// Avalia o desfecho de um paciente a partir do tempo real até o início do tratamento.
+pub fn avaliar_desfecho<R: Rng + ?Sized>(
+    condicao: &str,
+    porta_tratamento: Duration,
+    escala_tempo: f64,
+    rng: &mut R,
+) -> RegistroDesfecho {
+    let perfil = perfil_clinico(condicao);
+    let porta_tratamento = porta_tratamento.as_secs_f64() / escala_tempo;
+    let atraso = (porta_tratamento - perfil.prazo).max(0.0);
+
+    RegistroDesfecho {
+        condicao: condicao.to_string(),
+        porta_tratamento,
+        prazo: perfil.prazo,
+        desfecho: perfil.modelo.sortear(atraso, rng),
+    }
+}
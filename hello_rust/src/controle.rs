@@ -0,0 +1,142 @@
+// controle.rs
+use std::sync::Mutex as StdMutex;
+use tokio::sync::watch;
+use tokio::time::{sleep, Duration, Instant};
+
+/// Intervalo (em tempo real) com que as esperas controladas conferem pausa e velocidade.
+const PASSO_CONTROLE: Duration = Duration::from_millis(100);
+/// Limites do multiplicador de velocidade.
+pub const VELOCIDADE_MIN: f64 = 0.1;
+pub const VELOCIDADE_MAX: f64 = 10.0;
+
+/// Relógio simulado: avança `velocidade` segundos por segundo real enquanto a simulação roda.
+struct Relogio {
+    /// Tempo simulado acumulado até `desde`.
+    base: Duration,
+    /// Instante real a partir do qual o relógio corre (`None` enquanto pausado).
+    desde: Option<Instant>,
+    velocidade: f64,
+    /// Fim do passo único em andamento: ao alcançá-lo a simulação volta a pausar.
+    fim_passo: Option<Duration>,
+}
+
+impl Relogio {
+    fn agora(&self) -> Duration {
+        let corrido = self.desde.map_or(Duration::ZERO, |desde| desde.elapsed().mul_f64(self.velocidade));
+        let agora = self.base + corrido;
+        self.fim_passo.map_or(agora, |fim| agora.min(fim))
+    }
+
+    /// Incorpora à base o tempo corrido desde `desde` (antes de mudar o ritmo do relógio).
+    fn consolidar(&mut self) {
+        self.base = self.agora();
+        if self.desde.is_some() {
+            self.desde = Some(Instant::now());
+        }
+    }
+}
+
+/// Controle de execução compartilhado por todos os hospitais: pausa, retomada, passo único
+/// e velocidade. Enquanto pausada, nenhuma reserva é concedida e os tempos de uso e transporte
+/// não correm; todos os tempos da simulação são medidos pelo relógio simulado (`agora`).
+pub struct ControleSimulacao {
+    pausado: watch::Sender<bool>,
+    relogio: StdMutex<Relogio>,
+}
+
+impl Default for ControleSimulacao {
+    fn default() -> Self {
+        Self::novo()
+    }
+}
+
+impl ControleSimulacao {
+    pub fn novo() -> Self {
+        Self {
+            pausado: watch::Sender::new(false),
+            relogio: StdMutex::new(Relogio {
+                base: Duration::ZERO,
+                desde: Some(Instant::now()),
+                velocidade: 1.0,
+                fim_passo: None,
+            }),
+        }
+    }
+
+    pub fn pausar(&self) {
+        let mut relogio = self.relogio.lock().unwrap();
+        relogio.consolidar();
+        relogio.desde = None;
+        relogio.fim_passo = None;
+        self.pausado.send_replace(true);
+    }
+
+    pub fn retomar(&self) {
+        let mut relogio = self.relogio.lock().unwrap();
+        relogio.consolidar();
+        relogio.fim_passo = None;
+        relogio.desde.get_or_insert_with(Instant::now);
+        self.pausado.send_replace(false);
+    }
+
+    /// Roda a simulação por `duracao` de tempo simulado e pausa de novo.
+    pub fn passo(&self, duracao: Duration) {
+        let mut relogio = self.relogio.lock().unwrap();
+        relogio.consolidar();
+        relogio.fim_passo = Some(relogio.base + duracao);
+        relogio.desde.get_or_insert_with(Instant::now);
+        self.pausado.send_replace(false);
+    }
+
+    pub fn pausado(&self) -> bool {
+        *self.pausado.borrow()
+    }
+
+    pub fn velocidade(&self) -> f64 {
+        self.relogio.lock().unwrap().velocidade
+    }
+
+    /// Altera o multiplicador de velocidade (limitado a `VELOCIDADE_MIN..=VELOCIDADE_MAX`).
+    pub fn definir_velocidade(&self, velocidade: f64) {
+        let mut relogio = self.relogio.lock().unwrap();
+        relogio.consolidar();
+        relogio.velocidade = velocidade.clamp(VELOCIDADE_MIN, VELOCIDADE_MAX);
+    }
+
+    /// Tempo simulado desde o início. Encerra o passo único se o fim dele já foi alcançado.
+    pub fn agora(&self) -> Duration {
+        let mut relogio = self.relogio.lock().unwrap();
+        let agora = relogio.agora();
+        if relogio.fim_passo == Some(agora) {
+            relogio.base = agora;
+            relogio.desde = None;
+            relogio.fim_passo = None;
+            self.pausado.send_replace(true);
+        }
+        agora
+    }
+
+    /// Tempo simulado decorrido desde `inicio` (um valor anterior de `agora`).
+    pub fn decorrido(&self, inicio: Duration) -> Duration {
+        self.agora().saturating_sub(inicio)
+    }
+
+    /// Retorna imediatamente se a simulação está rodando; senão espera a retomada.
+    pub async fn aguardar_retomada(&self) {
+        let mut rx = self.pausado.subscribe();
+        let _ = rx.wait_for(|pausado| !*pausado).await;
+    }
+
+    /// Dorme `duracao` de tempo simulado: acompanha a velocidade e não conta o tempo pausado.
+    pub async fn dormir(&self, duracao: Duration) {
+        let alvo = self.agora() + duracao;
+        loop {
+            self.aguardar_retomada().await;
+            let falta = alvo.saturating_sub(self.agora());
+            if falta.is_zero() {
+                break;
+            }
+            sleep(falta.div_f64(self.velocidade()).min(PASSO_CONTROLE)).await;
+        }
+    }
+}
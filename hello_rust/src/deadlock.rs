@@ -0,0 +1,83 @@
+// deadlock.rs
+use std::collections::HashMap;
+use crate::recursos::TipoRecurso;
+
+/// Fotografia da alocação dos recursos num instante: o que está livre,
+/// o que cada paciente mantém e o que cada paciente está esperando.
+#[derive(Debug, Clone, Default)]
+pub struct RetratoAlocacao {
+    pub disponiveis: HashMap<TipoRecurso, usize>,
+    pub alocacao: HashMap<String, Vec<TipoRecurso>>,
+    pub pedidos: HashMap<String, Vec<TipoRecurso>>,
+}
+
+/// Verifica se `pedido` cabe no vetor de trabalho `work` (contando repetições).
+fn cabe(pedido: &[TipoRecurso], work: &HashMap<TipoRecurso, usize>) -> bool {
+    let mut contagem: HashMap<TipoRecurso, usize> = HashMap::new();
+    for tipo in pedido {
+        *contagem.entry(tipo).or_default() += 1;
+    }
+    contagem.iter().all(|(tipo, qtd)| work.get(tipo).copied().unwrap_or(0) >= *qtd)
+}
+
+/// Simula a liberação em cadeia: repetidamente escolhe um paciente cuja demanda cabe
+/// no que está livre e devolve o que ele mantém. Retorna quem nunca consegue terminar.
+fn quem_nao_termina(
+    disponiveis: &HashMap<TipoRecurso, usize>,
+    alocacao: &HashMap<String, Vec<TipoRecurso>>,
+    demanda: &HashMap<String, Vec<TipoRecurso>>,
+) -> Vec<String> {
+    let mut work = disponiveis.clone();
+    let mut pendentes: Vec<&String> = alocacao.keys().chain(demanda.keys()).collect();
+    pendentes.sort();
+    pendentes.dedup();
+
+    loop {
+        let vazio = vec![];
+        let Some(pos) = pendentes
+            .iter()
+            .position(|p| cabe(demanda.get(*p).unwrap_or(&vazio), &work))
+        else {
+            break;
+        };
+
+        let paciente = pendentes.remove(pos);
+        for tipo in alocacao.get(paciente).unwrap_or(&vazio) {
+            *work.entry(tipo).or_default() += 1;
+        }
+    }
+
+    pendentes.into_iter().cloned().collect()
+}
+
+/// Algoritmo de detecção para recursos com várias instâncias: retorna os pacientes
+/// em deadlock (vazio se nenhum). Quem não espera nada é considerado capaz de terminar.
+pub fn detectar_deadlock(retrato: &RetratoAlocacao) -> Vec<String> {
+    quem_nao_termina(&retrato.disponiveis, &retrato.alocacao, &retrato.pedidos)
+}
+
+/// Algoritmo do banqueiro: o estado é seguro se existe uma ordem em que todos os pacientes
+/// conseguem obter o restante da sua necessidade máxima declarada e terminar.
+/// Pacientes sem necessidade declarada são avaliados apenas pelo que estão pedindo.
+pub fn estado_seguro(retrato: &RetratoAlocacao, necessidades: &HashMap<String, Vec<TipoRecurso>>) -> bool {
+    let mut restante: HashMap<String, Vec<TipoRecurso>> = HashMap::new();
+    let pacientes = retrato.alocacao.keys().chain(retrato.pedidos.keys()).chain(necessidades.keys());
+
+    for paciente in pacientes {
+        let falta = match necessidades.get(paciente) {
+            Some(maximo) => {
+                let mut falta = maximo.clone();
+                for tipo in retrato.alocacao.get(paciente).into_iter().flatten() {
+                    if let Some(i) = falta.iter().position(|t| t == tipo) {
+                        falta.remove(i);
+                    }
+                }
+                falta
+            }
+            None => retrato.pedidos.get(paciente).cloned().unwrap_or_default(),
+        };
+        restante.insert(paciente.clone(), falta);
+    }
+
+    quem_nao_termina(&retrato.disponiveis, &retrato.alocacao, &restante).is_empty()
+}
@@ -1,10 +1,30 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::time::{Instant, Duration};
+use std::time::Duration;
+use crate::paciente::ResultadoAtendimento;
+use crate::clinica::{Desfecho, RegistroDesfecho};
+
+/// Taxas de desfecho clínico dos pacientes tratados (frações entre 0 e 1).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TaxasDesfecho {
+    pub tratados: usize,
+    pub bom: f64,
+    pub complicacao: f64,
+    pub obito: f64,
+    pub dentro_do_prazo: f64,
+}
 
 pub struct Estatisticas {
     // Registra o tempo total de atendimento por paciente (String)
     pub(crate) atendimentos: Arc<Mutex<HashMap<String, Duration>>>,
+    // Pacientes interrompidos por falha de reserva
+    pub(crate) abortados: Arc<Mutex<Vec<String>>>,
+    // Pacientes com o atendimento cancelado, com o motivo (operador, preempção ou prazo)
+    pub(crate) cancelamentos: Arc<Mutex<HashMap<String, String>>>,
+    // Pacientes que desistiram, com o motivo (balking/reneging)
+    pub(crate) desistencias: Arc<Mutex<HashMap<String, String>>>,
+    // Desfecho clínico de cada paciente tratado
+    pub(crate) desfechos: Arc<Mutex<HashMap<String, RegistroDesfecho>>>,
 }
 
 impl Estatisticas {
@@ -16,27 +36,70 @@ impl Estatisticas {
     pub fn novo() -> Self {
         Self {
             atendimentos: Arc::new(Mutex::new(HashMap::new())),
+            abortados: Arc::new(Mutex::new(vec![])),
+            cancelamentos: Arc::new(Mutex::new(HashMap::new())),
+            desistencias: Arc::new(Mutex::new(HashMap::new())),
+            desfechos: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    /// Marca o início do atendimento e retorna o instante inicial
-    pub fn iniciar_atendimento(&self, paciente: &str) -> Instant {
+    /// Marca o início do atendimento
+    pub fn iniciar_atendimento(&self, paciente: &str) {
         println!("⏱️ Início do atendimento: {}", paciente);
-        Instant::now()
     }
 
-    /// Registra o término do atendimento, calculando a duração
-    /// O parâmetro 'concluido' é crucial para ignorar pacientes abortados pelo monitor.
-    pub fn finalizar_atendimento(&self, paciente: &str, inicio: Instant, concluido: bool) {
-        let duracao = inicio.elapsed();
+    /// Registra o término do atendimento com a sua duração (em tempo simulado)
+    /// Apenas atendimentos concluídos entram no tempo médio; abortados, cancelados e desistências são contados à parte.
+    pub fn finalizar_atendimento(&self, paciente: &str, duracao: Duration, resultado: &ResultadoAtendimento) {
         
-        if concluido {
-            println!("✅ Fim do atendimento: {} ({:.2}s)", paciente, duracao.as_secs_f64());
-            let mut lock = self.atendimentos.lock().unwrap();
-            lock.insert(paciente.to_string(), duracao);
-        } else {
-            // Log de um atendimento cancelado/abortado, mas não adiciona às estatísticas
-            println!("❌ Atendimento CANCELADO/ABORTADO: {} ({:.2}s)", paciente, duracao.as_secs_f64());
+        match resultado {
+            ResultadoAtendimento::Concluido { .. } => {
+                println!("✅ Fim do atendimento: {} ({:.2}s)", paciente, duracao.as_secs_f64());
+                let mut lock = self.atendimentos.lock().unwrap();
+                lock.insert(paciente.to_string(), duracao);
+            }
+            ResultadoAtendimento::Abortado => {
+                // Log de um atendimento cancelado/abortado, mas não adiciona às estatísticas
+                println!("❌ Atendimento CANCELADO/ABORTADO: {} ({:.2}s)", paciente, duracao.as_secs_f64());
+                self.abortados.lock().unwrap().push(paciente.to_string());
+            }
+            ResultadoAtendimento::Cancelado(motivo) => {
+                println!("🛑 Atendimento CANCELADO: {} ({:.2}s) - {}", paciente, duracao.as_secs_f64(), motivo);
+                self.cancelamentos.lock().unwrap().insert(paciente.to_string(), motivo.to_string());
+            }
+            ResultadoAtendimento::Desistiu(motivo) => {
+                println!("🚪 Paciente DESISTIU: {} ({:.2}s) - {}", paciente, duracao.as_secs_f64(), motivo);
+                self.desistencias.lock().unwrap().insert(paciente.to_string(), motivo.to_string());
+            }
+        }
+    }
+
+    /// Registra o desfecho clínico de um paciente tratado.
+    pub fn registrar_desfecho(&self, paciente: &str, registro: RegistroDesfecho) {
+        println!(
+            "🩺 Desfecho {} ({}): {} | porta-tratamento {:.1} (prazo {:.1})",
+            paciente, registro.condicao, registro.desfecho, registro.porta_tratamento, registro.prazo
+        );
+        self.desfechos.lock().unwrap().insert(paciente.to_string(), registro);
+    }
+
+    /// Calcula as taxas de desfecho dos pacientes tratados até agora.
+    pub fn taxas_desfecho(&self) -> TaxasDesfecho {
+        let lock = self.desfechos.lock().unwrap();
+        let tratados = lock.len();
+        if tratados == 0 {
+            return TaxasDesfecho::default();
+        }
+
+        let fracao = |filtro: &dyn Fn(&RegistroDesfecho) -> bool| {
+            lock.values().filter(|r| filtro(r)).count() as f64 / tratados as f64
+        };
+        TaxasDesfecho {
+            tratados,
+            bom: fracao(&|r| r.desfecho == Desfecho::Bom),
+            complicacao: fracao(&|r| r.desfecho == Desfecho::Complicacao),
+            obito: fracao(&|r| r.desfecho == Desfecho::Obito),
+            dentro_do_prazo: fracao(&|r| r.dentro_do_prazo()),
         }
     }
 
@@ -61,5 +124,42 @@ impl Estatisticas {
         println!("\nTotal de atendimentos CONCLUÍDOS: {}", total_atendidos);
         println!("Tempo total acumulado: {:.2} segundos", total_tempo);
         println!("Tempo médio por paciente: {:.2} segundos", media);
+
+        let abortados = self.abortados.lock().unwrap();
+        println!("\nTotal de atendimentos ABORTADOS: {}", abortados.len());
+        for paciente in abortados.iter() {
+            println!(" - {}", paciente);
+        }
+
+        let cancelamentos = self.cancelamentos.lock().unwrap();
+        println!("\nTotal de atendimentos CANCELADOS: {}", cancelamentos.len());
+        for (paciente, motivo) in cancelamentos.iter() {
+            println!(" - {} → {}", paciente, motivo);
+        }
+        drop(cancelamentos);
+
+        let desistencias = self.desistencias.lock().unwrap();
+        println!("\nTotal de DESISTÊNCIAS (saíram sem atendimento): {}", desistencias.len());
+        for (paciente, motivo) in desistencias.iter() {
+            println!(" - {} → {}", paciente, motivo);
+        }
+        drop(desistencias);
+
+        println!("\n🩺 Desfechos Clínicos (porta-tratamento em unidades de tempo):");
+        for (paciente, registro) in self.desfechos.lock().unwrap().iter() {
+            println!(
+                " - {} ({}) → {} | {:.1} / prazo {:.1} (atraso {:.1})",
+                paciente, registro.condicao, registro.desfecho, registro.porta_tratamento, registro.prazo, registro.atraso()
+            );
+        }
+        let taxas = self.taxas_desfecho();
+        println!(
+            "Tratados: {} | Bom: {:.0}% | Complicação: {:.0}% | Óbito: {:.0}% | Dentro do prazo: {:.0}%",
+            taxas.tratados,
+            taxas.bom * 100.0,
+            taxas.complicacao * 100.0,
+            taxas.obito * 100.0,
+            taxas.dentro_do_prazo * 100.0
+        );
     }
 }
\ No newline at end of file
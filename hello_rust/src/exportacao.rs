@@ -0,0 +1,541 @@
+// exportacao.rs
+use std::fmt::Write as _;
+use std::path::Path;
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use eframe::egui::{self, Color32};
+use tiny_skia::{Paint, PathBuilder, Pixmap, Rect, Stroke, StrokeDash, Transform};
+use tokio::sync::Mutex;
+use crate::monitor_gui::CORES_RECURSOS;
+use crate::recursos::{EventoEspera, Recursos};
+use crate::{HistoricoRecursos, CORES_PACIENTES};
+
+/// Pixels do PNG por unidade da figura (o SVG é vetorial; o PNG sai com o dobro da resolução).
+const ESCALA_PNG: f32 = 2.0;
+/// Largura padrão das figuras exportadas.
+const LARGURA_FIGURA: f32 = 1000.0;
+/// Altura de cada linha da linha do tempo de ocupação.
+const ALTURA_LINHA: f32 = 18.0;
+/// Fonte usada no PNG (a mesma fonte proporcional embutida no egui).
+const FONTE_PNG: &str = "Ubuntu-Light";
+
+const COR_TEXTO: Color32 = Color32::from_rgb(40, 40, 40);
+const COR_EIXO: Color32 = Color32::from_rgb(90, 90, 90);
+const COR_GRADE: Color32 = Color32::from_rgb(225, 225, 225);
+
+// ---------------- Figura (primitivas comuns ao SVG e ao PNG) ----------------
+
+/// Alinhamento horizontal de um texto em relação ao seu ponto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ancora {
+    Inicio,
+    Meio,
+    Fim,
+}
+
+impl Ancora {
+    fn svg(self) -> &'static str {
+        match self {
+            Ancora::Inicio => "start",
+            Ancora::Meio => "middle",
+            Ancora::Fim => "end",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Elemento {
+    /// Retângulo preenchido; `dica` vira um `<title>` no SVG (aparece ao passar o mouse).
+    Retangulo { x: f32, y: f32, largura: f32, altura: f32, cor: Color32, contorno: Option<Color32>, dica: Option<String> },
+    Linha { pontos: Vec<(f32, f32)>, cor: Color32, espessura: f32, tracejada: bool },
+    /// Texto com a linha de base em `y`.
+    Texto { x: f32, y: f32, texto: String, tamanho: f32, cor: Color32, ancora: Ancora },
+}
+
+/// Gráfico independente da GUI: montado a partir dos históricos e gravado em SVG e PNG,
+/// sem precisar de janela.
+#[derive(Debug, Clone)]
+pub struct Figura {
+    largura: f32,
+    altura: f32,
+    elementos: Vec<Elemento>,
+}
+
+impl Figura {
+    fn nova(largura: f32, altura: f32) -> Self {
+        Self { largura, altura, elementos: vec![] }
+    }
+
+    fn retangulo(&mut self, x: f32, y: f32, largura: f32, altura: f32, cor: Color32) {
+        self.elementos.push(Elemento::Retangulo { x, y, largura, altura, cor, contorno: None, dica: None });
+    }
+
+    fn linha(&mut self, pontos: Vec<(f32, f32)>, cor: Color32, espessura: f32, tracejada: bool) {
+        self.elementos.push(Elemento::Linha { pontos, cor, espessura, tracejada });
+    }
+
+    fn texto(&mut self, x: f32, y: f32, texto: impl Into<String>, tamanho: f32, ancora: Ancora) {
+        self.texto_colorido(x, y, texto, tamanho, COR_TEXTO, ancora);
+    }
+
+    fn texto_colorido(&mut self, x: f32, y: f32, texto: impl Into<String>, tamanho: f32, cor: Color32, ancora: Ancora) {
+        self.elementos.push(Elemento::Texto { x, y, texto: texto.into(), tamanho, cor, ancora });
+    }
+
+    fn titulo(&mut self, texto: &str) {
+        self.texto(self.largura / 2.0, 24.0, texto, 16.0, Ancora::Meio);
+    }
+
+    /// Legenda em linha (quadrado colorido + nome), alinhada à direita no topo da figura.
+    fn legenda(&mut self, itens: &[(String, Color32)]) {
+        let larguras: Vec<f32> = itens.iter().map(|(nome, _)| 18.0 + largura_estimada(nome, 11.0) + 14.0).collect();
+        let mut x = self.largura - 20.0 - larguras.iter().sum::<f32>();
+        for ((nome, cor), largura) in itens.iter().zip(larguras) {
+            self.retangulo(x, 38.0, 12.0, 12.0, *cor);
+            self.texto(x + 18.0, 48.0, nome.clone(), 11.0, Ancora::Inicio);
+            x += largura;
+        }
+    }
+
+    /// Documento SVG da figura.
+    pub fn svg(&self) -> String {
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="sans-serif">"#,
+            self.largura, self.altura
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        for elemento in &self.elementos {
+            match elemento {
+                Elemento::Retangulo { x, y, largura, altura, cor, contorno, dica } => {
+                    let _ = write!(
+                        svg,
+                        r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" {}"#,
+                        x, y, largura, altura, atributos_cor("fill", *cor)
+                    );
+                    if let Some(contorno) = contorno {
+                        let _ = write!(svg, r#" {} stroke-width="0.5""#, atributos_cor("stroke", *contorno));
+                    }
+                    match dica {
+                        Some(dica) => { let _ = writeln!(svg, "><title>{}</title></rect>", escapar(dica)); }
+                        None => { let _ = writeln!(svg, "/>"); }
+                    }
+                }
+                Elemento::Linha { pontos, cor, espessura, tracejada } => {
+                    let pontos: Vec<String> = pontos.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+                    let _ = writeln!(
+                        svg,
+                        r#"<polyline points="{}" fill="none" {} stroke-width="{}"{}/>"#,
+                        pontos.join(" "),
+                        atributos_cor("stroke", *cor),
+                        espessura,
+                        if *tracejada { r#" stroke-dasharray="6 4""# } else { "" }
+                    );
+                }
+                Elemento::Texto { x, y, texto, tamanho, cor, ancora } => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<text x="{:.1}" y="{:.1}" font-size="{}" text-anchor="{}" {}>{}</text>"#,
+                        x, y, tamanho, ancora.svg(), atributos_cor("fill", *cor), escapar(texto)
+                    );
+                }
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Imagem PNG da figura (RGBA, `ESCALA_PNG` pixels por unidade).
+    pub fn png(&self) -> Result<Vec<u8>, String> {
+        let mut pixmap = Pixmap::new((self.largura * ESCALA_PNG).ceil() as u32, (self.altura * ESCALA_PNG).ceil() as u32)
+            .ok_or("dimensões inválidas para o PNG")?;
+        pixmap.fill(tiny_skia::Color::WHITE);
+        let fontes = egui::FontDefinitions::default();
+        let dados_fonte = fontes.font_data.get(FONTE_PNG).ok_or("fonte do egui não encontrada")?;
+        let fonte = FontRef::try_from_slice(&dados_fonte.font).map_err(|e| e.to_string())?;
+        let transformacao = Transform::from_scale(ESCALA_PNG, ESCALA_PNG);
+
+        for elemento in &self.elementos {
+            match elemento {
+                Elemento::Retangulo { x, y, largura, altura, cor, contorno, .. } => {
+                    let Some(retangulo) = Rect::from_xywh(*x, *y, largura.max(0.1), altura.max(0.1)) else { continue };
+                    pixmap.fill_rect(retangulo, &pincel(*cor), transformacao, None);
+                    if let Some(contorno) = contorno {
+                        let caminho = PathBuilder::from_rect(retangulo);
+                        let traco = Stroke { width: 0.5, ..Default::default() };
+                        pixmap.stroke_path(&caminho, &pincel(*contorno), &traco, transformacao, None);
+                    }
+                }
+                Elemento::Linha { pontos, cor, espessura, tracejada } => {
+                    let mut caminho = PathBuilder::new();
+                    for (i, (x, y)) in pontos.iter().enumerate() {
+                        if i == 0 { caminho.move_to(*x, *y) } else { caminho.line_to(*x, *y) }
+                    }
+                    let Some(caminho) = caminho.finish() else { continue };
+                    let traco = Stroke {
+                        width: *espessura,
+                        dash: if *tracejada { StrokeDash::new(vec![6.0, 4.0], 0.0) } else { None },
+                        ..Default::default()
+                    };
+                    pixmap.stroke_path(&caminho, &pincel(*cor), &traco, transformacao, None);
+                }
+                Elemento::Texto { x, y, texto, tamanho, cor, ancora } => {
+                    desenhar_texto(&mut pixmap, &fonte, texto, (*x, *y), *tamanho, *cor, *ancora);
+                }
+            }
+        }
+
+        let mut bytes = vec![];
+        let mut codificador = png::Encoder::new(&mut bytes, pixmap.width(), pixmap.height());
+        codificador.set_color(png::ColorType::Rgba);
+        codificador.set_depth(png::BitDepth::Eight);
+        let mut escritor = codificador.write_header().map_err(|e| e.to_string())?;
+        // Fundo opaco: os pixels pré-multiplicados do tiny-skia já são RGBA comuns
+        escritor.write_image_data(pixmap.data()).map_err(|e| e.to_string())?;
+        escritor.finish().map_err(|e| e.to_string())?;
+        Ok(bytes)
+    }
+
+    /// Grava `<base>.svg` e `<base>.png`.
+    pub fn salvar(&self, base: &Path) -> std::io::Result<()> {
+        std::fs::write(base.with_extension("svg"), self.svg())?;
+        std::fs::write(base.with_extension("png"), self.png().map_err(std::io::Error::other)?)
+    }
+}
+
+fn atributos_cor(atributo: &str, cor: Color32) -> String {
+    let [r, g, b, a] = cor.to_srgba_unmultiplied();
+    if a == 255 {
+        format!(r##"{}="#{:02x}{:02x}{:02x}""##, atributo, r, g, b)
+    } else {
+        format!(r##"{0}="#{1:02x}{2:02x}{3:02x}" {0}-opacity="{4:.2}""##, atributo, r, g, b, a as f32 / 255.0)
+    }
+}
+
+pub(crate) fn escapar(texto: &str) -> String {
+    texto.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn pincel(cor: Color32) -> Paint<'static> {
+    let [r, g, b, a] = cor.to_srgba_unmultiplied();
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, a);
+    paint.anti_alias = true;
+    paint
+}
+
+/// Largura aproximada de um texto (para decidir se um nome cabe numa barra e montar legendas).
+fn largura_estimada(texto: &str, tamanho: f32) -> f32 {
+    texto.chars().count() as f32 * tamanho * 0.55
+}
+
+/// Rasteriza um texto no PNG, misturando a cobertura de cada glifo com o fundo.
+fn desenhar_texto(pixmap: &mut Pixmap, fonte: &FontRef, texto: &str, (x, y): (f32, f32), tamanho: f32, cor: Color32, ancora: Ancora) {
+    let escalada = fonte.as_scaled(PxScale::from(tamanho * ESCALA_PNG));
+    let glifos: Vec<_> = texto.chars().map(|c| escalada.glyph_id(c)).collect();
+    let largura: f32 = glifos.iter().map(|id| escalada.h_advance(*id)).sum::<f32>()
+        + glifos.windows(2).map(|par| escalada.kern(par[0], par[1])).sum::<f32>();
+    let mut cursor = x * ESCALA_PNG - match ancora {
+        Ancora::Inicio => 0.0,
+        Ancora::Meio => largura / 2.0,
+        Ancora::Fim => largura,
+    };
+
+    let (largura_px, altura_px) = (pixmap.width() as i32, pixmap.height() as i32);
+    let dados = pixmap.data_mut();
+    let [r, g, b, a] = cor.to_srgba_unmultiplied();
+    let mut anterior = None;
+    for id in glifos {
+        if let Some(anterior) = anterior {
+            cursor += escalada.kern(anterior, id);
+        }
+        let glifo = id.with_scale_and_position(escalada.scale(), point(cursor, y * ESCALA_PNG));
+        cursor += escalada.h_advance(id);
+        anterior = Some(id);
+
+        let Some(contorno) = fonte.outline_glyph(glifo) else { continue };
+        let limites = contorno.px_bounds();
+        contorno.draw(|gx, gy, cobertura| {
+            let (px, py) = (limites.min.x as i32 + gx as i32, limites.min.y as i32 + gy as i32);
+            if px < 0 || py < 0 || px >= largura_px || py >= altura_px {
+                return;
+            }
+            let alfa = cobertura.clamp(0.0, 1.0) * a as f32 / 255.0;
+            let indice = (py * largura_px + px) as usize * 4;
+            for (canal, valor) in [r, g, b].into_iter().enumerate() {
+                let fundo = dados[indice + canal] as f32;
+                dados[indice + canal] = (fundo + (valor as f32 - fundo) * alfa).round() as u8;
+            }
+        });
+    }
+}
+
+// ---------------- Eixos ----------------
+
+/// Passo "redondo" (1, 2 ou 5 × 10^n) para dividir `[0, maximo]` em cerca de `divisoes` partes.
+fn passo_redondo(maximo: f64, divisoes: usize) -> f64 {
+    let bruto = (maximo / divisoes as f64).max(f64::EPSILON);
+    let magnitude = 10f64.powf(bruto.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].into_iter().map(|m| m * magnitude).find(|passo| *passo >= bruto).unwrap_or(10.0 * magnitude)
+}
+
+fn formatar_marca(valor: f64) -> String {
+    if valor.fract() == 0.0 { format!("{}", valor) } else { format!("{:.1}", valor) }
+}
+
+/// Área de plotagem: converte valores (x e y a partir de zero) em coordenadas da figura.
+struct Eixos {
+    esquerda: f32,
+    topo: f32,
+    largura: f32,
+    altura: f32,
+    x_max: f64,
+    y_max: f64,
+}
+
+impl Eixos {
+    fn px(&self, x: f64) -> f32 {
+        self.esquerda + (x / self.x_max) as f32 * self.largura
+    }
+
+    fn py(&self, y: f64) -> f32 {
+        self.topo + self.altura - (y / self.y_max) as f32 * self.altura
+    }
+
+    fn base(&self) -> f32 {
+        self.topo + self.altura
+    }
+
+    /// Eixo x com marcas e rótulo; com `rotulo_y`, também o eixo y com linhas de grade.
+    fn desenhar(&self, figura: &mut Figura, rotulo_x: &str, rotulo_y: Option<&str>) {
+        let passo = passo_redondo(self.x_max, 10);
+        let mut x = 0.0;
+        while x <= self.x_max + f64::EPSILON {
+            let px = self.px(x);
+            figura.linha(vec![(px, self.base()), (px, self.base() + 4.0)], COR_EIXO, 1.0, false);
+            figura.texto(px, self.base() + 16.0, formatar_marca(x), 10.0, Ancora::Meio);
+            x += passo;
+        }
+        figura.linha(vec![(self.esquerda, self.base()), (self.esquerda + self.largura, self.base())], COR_EIXO, 1.0, false);
+        figura.texto(self.esquerda + self.largura / 2.0, self.base() + 34.0, rotulo_x, 11.0, Ancora::Meio);
+
+        let Some(rotulo_y) = rotulo_y else { return };
+        let passo = passo_redondo(self.y_max, 5).max(1.0);
+        let mut y = 0.0;
+        while y <= self.y_max + f64::EPSILON {
+            let py = self.py(y);
+            if y > 0.0 {
+                figura.linha(vec![(self.esquerda, py), (self.esquerda + self.largura, py)], COR_GRADE, 1.0, false);
+            }
+            figura.texto(self.esquerda - 6.0, py + 3.5, formatar_marca(y), 10.0, Ancora::Fim);
+            y += passo;
+        }
+        figura.linha(vec![(self.esquerda, self.topo), (self.esquerda, self.base())], COR_EIXO, 1.0, false);
+        figura.texto(self.esquerda, self.topo - 10.0, rotulo_y, 11.0, Ancora::Inicio);
+    }
+}
+
+// ---------------- Gráficos ----------------
+
+/// Linha do tempo de ocupação (como a aba de gráficos): uma linha por instância de cada
+/// recurso, uma barra por uso, com o nome do paciente quando cabe e os detalhes como dica no SVG.
+pub async fn grafico_ocupacao(recursos: &Recursos) -> Figura {
+    let agora = recursos.controle.agora().as_secs_f64();
+    let mut linhas: Vec<(String, bool)> = vec![];
+    let mut barras = vec![];
+    let mut pacientes: Vec<String> = vec![];
+    for pool in &recursos.pools {
+        let eventos = match recursos.historico_uso.eventos(pool.config.tipo) {
+            Some(eventos) => eventos.lock().await.clone(),
+            None => continue,
+        };
+        let instancias = eventos.iter().map(|e| e.instancia_id).max().unwrap_or(0).max(pool.capacidade());
+        let primeira = linhas.len();
+        linhas.push((pool.config.nome.to_string(), true));
+        linhas.extend((1..=instancias).map(|i| (format!("{} {}", pool.config.instancia, i), false)));
+        for evento in eventos {
+            if !pacientes.contains(&evento.nome_paciente) {
+                pacientes.push(evento.nome_paciente.clone());
+            }
+            let recurso = format!("{} {}", pool.config.instancia, evento.instancia_id);
+            barras.push((primeira + evento.instancia_id, recurso, evento));
+        }
+    }
+
+    let topo = 50.0;
+    let mut figura = Figura::nova(LARGURA_FIGURA, topo + linhas.len() as f32 * ALTURA_LINHA + 50.0);
+    figura.titulo("Ocupação dos recursos ao longo do tempo");
+    let eixos = Eixos {
+        esquerda: 130.0,
+        topo,
+        largura: LARGURA_FIGURA - 150.0,
+        altura: linhas.len() as f32 * ALTURA_LINHA,
+        x_max: agora.max(1.0),
+        y_max: 1.0,
+    };
+
+    for (i, (rotulo, cabecalho)) in linhas.iter().enumerate() {
+        let y = topo + i as f32 * ALTURA_LINHA;
+        if *cabecalho {
+            figura.retangulo(0.0, y, LARGURA_FIGURA, ALTURA_LINHA, Color32::from_rgb(242, 242, 242));
+        }
+        let (x, cor) = if *cabecalho { (8.0, COR_TEXTO) } else { (20.0, COR_EIXO) };
+        figura.texto_colorido(x, y + ALTURA_LINHA * 0.7, rotulo.clone(), 11.0, cor, Ancora::Inicio);
+    }
+
+    for (linha, recurso, evento) in barras {
+        let em_uso = evento.fim == 0.0;
+        let fim = if em_uso { agora } else { evento.fim };
+        let (x, largura) = (eixos.px(evento.inicio), eixos.px(fim) - eixos.px(evento.inicio));
+        let y = topo + linha as f32 * ALTURA_LINHA + 2.0;
+        let indice = pacientes.iter().position(|p| *p == evento.nome_paciente).unwrap_or(0);
+        figura.elementos.push(Elemento::Retangulo {
+            x,
+            y,
+            largura,
+            altura: ALTURA_LINHA - 4.0,
+            cor: CORES_PACIENTES[indice % CORES_PACIENTES.len()],
+            contorno: Some(Color32::BLACK),
+            dica: Some(format!(
+                "Paciente: {}\nEtapa: {}\nRecurso: {}\nInício: {:.1}s\nFim: {}\nDuração: {:.1}s",
+                evento.nome_paciente,
+                evento.etapa,
+                recurso,
+                evento.inicio,
+                if em_uso { "em uso".to_string() } else { format!("{:.1}s", fim) },
+                fim - evento.inicio,
+            )),
+        });
+        if largura > largura_estimada(&evento.nome_paciente, 9.0) + 4.0 {
+            figura.texto_colorido(x + 3.0, y + ALTURA_LINHA * 0.55, evento.nome_paciente.clone(), 9.0, Color32::WHITE, Ancora::Inicio);
+        }
+    }
+
+    eixos.desenhar(&mut figura, "Tempo simulado (s)", None);
+    figura
+}
+
+/// Uso de cada tipo de recurso ao longo do tempo (instâncias ocupadas), com a capacidade tracejada.
+pub fn grafico_uso(historico: &HistoricoRecursos, recursos: &Recursos) -> Figura {
+    let mut figura = Figura::nova(LARGURA_FIGURA, 420.0);
+    figura.titulo("Recursos em uso ao longo do tempo");
+    let Some(ultimo) = historico.snapshots.last() else {
+        figura.texto(LARGURA_FIGURA / 2.0, 210.0, "Nenhum registro de uso", 12.0, Ancora::Meio);
+        return figura;
+    };
+
+    let capacidade_max = historico.snapshots.iter().flat_map(|s| s.capacidades.iter().copied()).max().unwrap_or(1);
+    let eixos = Eixos {
+        esquerda: 60.0,
+        topo: 70.0,
+        largura: LARGURA_FIGURA - 90.0,
+        altura: 290.0,
+        x_max: ultimo.tempo.max(1.0),
+        y_max: (capacidade_max as f64 + 1.0).max(1.0),
+    };
+    eixos.desenhar(&mut figura, "Tempo simulado (s)", Some("Em uso"));
+
+    let mut legenda = vec![];
+    for (i, (tipo, _)) in ultimo.disponiveis.iter().enumerate() {
+        let cor = CORES_RECURSOS[i % CORES_RECURSOS.len()];
+        let nome = recursos.pool(tipo).map_or(tipo.to_string(), |p| p.config.nome.to_string());
+        legenda.push((nome, cor));
+
+        let mut uso = vec![];
+        let mut capacidade = vec![];
+        for snapshot in &historico.snapshots {
+            let (Some((_, disponiveis)), Some(cap)) = (snapshot.disponiveis.get(i), snapshot.capacidades.get(i)) else { continue };
+            let x = eixos.px(snapshot.tempo);
+            uso.push((x, eixos.py(cap.saturating_sub(*disponiveis) as f64)));
+            capacidade.push((x, eixos.py(*cap as f64)));
+        }
+        figura.linha(capacidade, cor.gamma_multiply(0.5), 1.0, true);
+        figura.linha(uso, cor, 2.0, false);
+    }
+    figura.legenda(&legenda);
+    figura
+}
+
+/// Histograma dos tempos de espera em fila (esperas encerradas), empilhado por tipo de recurso.
+pub fn grafico_esperas(esperas: &[EventoEspera], recursos: &Recursos) -> Figura {
+    let mut figura = Figura::nova(LARGURA_FIGURA, 420.0);
+    figura.titulo("Distribuição dos tempos de espera em fila");
+    let duracoes: Vec<(usize, f64)> = esperas
+        .iter()
+        .filter(|e| e.fim > 0.0)
+        .filter_map(|e| recursos.pools.iter().position(|p| p.config.tipo == e.tipo).map(|i| (i, e.fim - e.inicio)))
+        .collect();
+    if duracoes.is_empty() {
+        figura.texto(LARGURA_FIGURA / 2.0, 210.0, "Nenhuma espera registrada", 12.0, Ancora::Meio);
+        return figura;
+    }
+
+    // Faixas de mesma largura; a última fecha no maior tempo de espera
+    let maior = duracoes.iter().map(|(_, d)| *d).fold(0.0, f64::max).max(1.0);
+    let largura_faixa = passo_redondo(maior, 12);
+    let faixas = (maior / largura_faixa).floor() as usize + 1;
+    let mut contagem = vec![vec![0usize; recursos.pools.len()]; faixas];
+    for (recurso, duracao) in &duracoes {
+        contagem[((duracao / largura_faixa) as usize).min(faixas - 1)][*recurso] += 1;
+    }
+
+    let maior_faixa = contagem.iter().map(|c| c.iter().sum::<usize>()).max().unwrap_or(1);
+    let eixos = Eixos {
+        esquerda: 60.0,
+        topo: 70.0,
+        largura: LARGURA_FIGURA - 90.0,
+        altura: 290.0,
+        x_max: faixas as f64 * largura_faixa,
+        y_max: maior_faixa as f64 * 1.1,
+    };
+    eixos.desenhar(&mut figura, "Tempo de espera (s)", Some("Esperas"));
+
+    for (f, por_recurso) in contagem.iter().enumerate() {
+        let (inicio, fim) = (f as f64 * largura_faixa, (f + 1) as f64 * largura_faixa);
+        let mut acumulado = 0;
+        for (r, quantidade) in por_recurso.iter().enumerate().filter(|(_, q)| **q > 0) {
+            let (y_topo, y_base) = (eixos.py((acumulado + quantidade) as f64), eixos.py(acumulado as f64));
+            figura.elementos.push(Elemento::Retangulo {
+                x: eixos.px(inicio) + 1.0,
+                y: y_topo,
+                largura: eixos.px(fim) - eixos.px(inicio) - 2.0,
+                altura: y_base - y_topo,
+                cor: CORES_RECURSOS[r % CORES_RECURSOS.len()],
+                contorno: Some(Color32::WHITE),
+                dica: Some(format!(
+                    "{}: {} espera(s) entre {}s e {}s",
+                    recursos.pools[r].config.nome, quantidade, formatar_marca(inicio), formatar_marca(fim)
+                )),
+            });
+            acumulado += quantidade;
+        }
+    }
+
+    let legenda: Vec<(String, Color32)> = recursos
+        .pools
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| duracoes.iter().any(|(r, _)| r == i))
+        .map(|(i, p)| (p.config.nome.to_string(), CORES_RECURSOS[i % CORES_RECURSOS.len()]))
+        .collect();
+    figura.legenda(&legenda);
+    figura
+}
+
+/// Exporta os gráficos do fim da execução (ocupação, uso e esperas) para `pasta`, em SVG e PNG.
+pub async fn exportar_graficos(recursos: &Recursos, historico: &Mutex<HistoricoRecursos>, pasta: &str) -> std::io::Result<()> {
+    let pasta = Path::new(pasta);
+    std::fs::create_dir_all(pasta)?;
+
+    let esperas = recursos.historico_uso.esperas.lock().unwrap().clone();
+    let figuras = [
+        ("ocupacao_recursos", grafico_ocupacao(recursos).await),
+        ("uso_recursos", grafico_uso(&*historico.lock().await, recursos)),
+        ("tempos_espera", grafico_esperas(&esperas, recursos)),
+    ];
+    for (nome, figura) in &figuras {
+        figura.salvar(&pasta.join(nome))?;
+    }
+    println!("✅ Gráficos exportados (SVG e PNG) em: {}", pasta.display());
+    Ok(())
+}
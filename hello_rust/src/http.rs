@@ -0,0 +1,86 @@
+// http.rs
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout, Duration};
+
+/// Tamanho máximo aceito para a linha de requisição e os cabeçalhos.
+const LIMITE_CABECALHOS: usize = 16 * 1024;
+/// Pausa entre duas espiadas quando o resto da linha de requisição ainda não chegou.
+const INTERVALO_ESPIADA: Duration = Duration::from_millis(5);
+/// Prazo para a linha de requisição chegar inteira.
+const PRAZO_ESPIADA: Duration = Duration::from_secs(5);
+
+/// Requisição HTTP/1.1 mínima: só o que os endpoints locais precisam (sem corpo).
+#[derive(Debug)]
+pub struct Requisicao {
+    pub metodo: String,
+    pub caminho: String,
+}
+
+/// Lê a linha de requisição e descarta os cabeçalhos. Retorna `None` se a conexão fechar antes
+/// ou se a requisição for malformada.
+pub async fn ler_requisicao(stream: &mut TcpStream) -> Option<Requisicao> {
+    let mut leitor = BufReader::new(stream);
+    let mut linha = String::new();
+    let mut lidos = 0;
+
+    lidos += leitor.read_line(&mut linha).await.ok()?;
+    let requisicao = interpretar_linha(&linha)?;
+
+    loop {
+        linha.clear();
+        let n = leitor.read_line(&mut linha).await.ok()?;
+        lidos += n;
+        if n == 0 || lidos > LIMITE_CABECALHOS {
+            return None;
+        }
+        if linha.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    Some(requisicao)
+}
+
+/// Lê a linha de requisição sem consumi-la, para quem precisa repassar a conexão intacta
+/// (ex.: o handshake do WebSocket). Retorna `None` se a linha não chegar inteira dentro do
+/// prazo, se a conexão fechar antes ou se passar do limite de cabeçalhos.
+pub async fn espiar_requisicao(stream: &TcpStream) -> Option<Requisicao> {
+    let mut inicio = vec![0u8; LIMITE_CABECALHOS];
+    let espiar = async {
+        loop {
+            let lidos = stream.peek(&mut inicio).await.ok()?;
+            if let Some(fim) = inicio[..lidos].iter().position(|b| *b == b'\n') {
+                return interpretar_linha(std::str::from_utf8(&inicio[..fim]).ok()?);
+            }
+            if lidos == 0 || lidos == inicio.len() {
+                return None;
+            }
+            // A espiada devolve na hora o que já chegou: espera o resto antes de tentar de novo
+            sleep(INTERVALO_ESPIADA).await;
+        }
+    };
+    timeout(PRAZO_ESPIADA, espiar).await.ok()?
+}
+
+/// Separa método e caminho da linha de requisição (`GET /caminho HTTP/1.1`).
+fn interpretar_linha(linha: &str) -> Option<Requisicao> {
+    let mut partes = linha.split_whitespace();
+    let metodo = partes.next()?.to_string();
+    // Descarta a query string: os endpoints não recebem parâmetros
+    let caminho = partes.next()?.split('?').next()?.to_string();
+    Some(Requisicao { metodo, caminho })
+}
+
+/// Escreve uma resposta completa e encerra a escrita (`Connection: close`).
+pub async fn responder(stream: &mut TcpStream, status: &str, tipo_conteudo: &str, corpo: &[u8]) -> std::io::Result<()> {
+    let cabecalho = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        tipo_conteudo,
+        corpo.len()
+    );
+    stream.write_all(cabecalho.as_bytes()).await?;
+    stream.write_all(corpo).await?;
+    stream.shutdown().await
+}
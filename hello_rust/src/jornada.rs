@@ -0,0 +1,308 @@
+// jornada.rs
+use std::collections::HashMap;
+use std::sync::Arc;
+use eframe::egui;
+use egui_plot::{Bar, BarChart, Plot, PlotPoint, Text};
+use crate::monitor_gui::CORES_RECURSOS;
+use crate::rede::RedeHospitalar;
+
+/// Intervalos sem fila nem recurso menores que isto (em segundos) não aparecem como ociosos.
+const LIMIAR_OCIOSO: f64 = 0.05;
+
+/// O que o paciente fazia num trecho da jornada.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TipoTrecho {
+    /// Aguardando na fila de um recurso.
+    Espera,
+    /// Usando uma instância de recurso.
+    Uso,
+    /// Sem fila nem recurso (entre etapas, em transporte).
+    Ocioso,
+}
+
+/// Trecho contínuo da jornada de um paciente.
+#[derive(Debug, Clone)]
+struct Trecho {
+    tipo: TipoTrecho,
+    descricao: String,
+    inicio: f64,
+    fim: f64,
+    em_andamento: bool,
+    /// Índice do recurso na configuração (define a cor).
+    recurso: usize,
+}
+
+/// Jornada de um paciente: todos os trechos, em ordem de início.
+struct Jornada {
+    paciente: String,
+    trechos: Vec<Trecho>,
+}
+
+impl Jornada {
+    fn inicio(&self) -> f64 {
+        self.trechos.iter().map(|t| t.inicio).fold(f64::INFINITY, f64::min)
+    }
+
+    fn fim(&self) -> f64 {
+        self.trechos.iter().map(|t| t.fim).fold(0.0, f64::max)
+    }
+
+    /// Tempo total nos trechos de um tipo. Esperas e usos simultâneos (ex.: a equipe cirúrgica)
+    /// são contados uma vez só.
+    fn total(&self, tipo: TipoTrecho) -> f64 {
+        uniao(self.trechos.iter().filter(|t| t.tipo == tipo).map(|t| (t.inicio, t.fim)))
+            .iter()
+            .fold(0.0, |total, (inicio, fim)| total + (fim - inicio))
+    }
+}
+
+/// Une intervalos sobrepostos, devolvendo-os em ordem.
+pub(crate) fn uniao(intervalos: impl Iterator<Item = (f64, f64)>) -> Vec<(f64, f64)> {
+    let mut intervalos: Vec<(f64, f64)> = intervalos.collect();
+    intervalos.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut unidos: Vec<(f64, f64)> = vec![];
+    for (inicio, fim) in intervalos {
+        match unidos.last_mut() {
+            Some(ultimo) if inicio <= ultimo.1 => ultimo.1 = ultimo.1.max(fim),
+            _ => unidos.push((inicio, fim)),
+        }
+    }
+    unidos
+}
+
+/// Distribui trechos (em ordem de início) em faixas sem sobreposição; retorna a faixa de cada um.
+fn distribuir_em_faixas(trechos: &[&Trecho]) -> (Vec<usize>, usize) {
+    let mut fins: Vec<f64> = vec![];
+    let faixas = trechos
+        .iter()
+        .map(|trecho| match fins.iter().position(|fim| *fim <= trecho.inicio) {
+            Some(faixa) => {
+                fins[faixa] = trecho.fim;
+                faixa
+            }
+            None => {
+                fins.push(trecho.fim);
+                fins.len() - 1
+            }
+        })
+        .collect();
+    (faixas, fins.len().max(1))
+}
+
+/// Monta a jornada de todos os pacientes a partir do histórico de uso e de espera de cada
+/// hospital da rede. Intervalos ainda abertos terminam em `agora`. Usa `blocking_lock`:
+/// deve ser chamada fora do runtime (thread da GUI).
+fn montar_jornadas(rede: &RedeHospitalar, agora: f64) -> Vec<Jornada> {
+    let mut trechos: HashMap<String, Vec<Trecho>> = HashMap::new();
+
+    for hospital in &rede.hospitais {
+        let sufixo = if rede.hospitais.len() > 1 { format!(" - {}", hospital.nome) } else { String::new() };
+        let recursos = &hospital.recursos;
+        let indice_de = |tipo: &str| recursos.pools.iter().position(|p| p.config.tipo == tipo).unwrap_or(0);
+
+        for espera in recursos.historico_uso.esperas.lock().unwrap().iter() {
+            let Some(pool) = recursos.pool(espera.tipo) else { continue };
+            let em_andamento = espera.fim == 0.0;
+            trechos.entry(espera.nome_paciente.clone()).or_default().push(Trecho {
+                tipo: TipoTrecho::Espera,
+                descricao: format!("Fila de {} ({}){}", pool.config.nome, espera.etapa, sufixo),
+                inicio: espera.inicio,
+                fim: if em_andamento { agora } else { espera.fim },
+                em_andamento,
+                recurso: indice_de(espera.tipo),
+            });
+        }
+
+        for pool in &recursos.pools {
+            let Some(eventos) = recursos.historico_uso.eventos(pool.config.tipo) else { continue };
+            for evento in eventos.blocking_lock().iter() {
+                let em_andamento = evento.fim == 0.0;
+                trechos.entry(evento.nome_paciente.clone()).or_default().push(Trecho {
+                    tipo: TipoTrecho::Uso,
+                    descricao: format!("{} {} ({}){}", pool.config.instancia, evento.instancia_id, evento.etapa, sufixo),
+                    inicio: evento.inicio,
+                    fim: if em_andamento { agora } else { evento.fim },
+                    em_andamento,
+                    recurso: indice_de(pool.config.tipo),
+                });
+            }
+        }
+    }
+
+    // Lacunas entre os trechos: o paciente não estava em fila nem usando recurso
+    let mut jornadas: Vec<Jornada> = trechos
+        .into_iter()
+        .map(|(paciente, trechos)| Jornada { paciente, trechos })
+        .collect();
+    for jornada in &mut jornadas {
+        let ocupados = uniao(jornada.trechos.iter().map(|t| (t.inicio, t.fim)));
+        for par in ocupados.windows(2) {
+            let (fim_anterior, inicio_seguinte) = (par[0].1, par[1].0);
+            if inicio_seguinte - fim_anterior >= LIMIAR_OCIOSO {
+                jornada.trechos.push(Trecho {
+                    tipo: TipoTrecho::Ocioso,
+                    descricao: "Sem fila nem recurso (entre etapas ou em transporte)".to_string(),
+                    inicio: fim_anterior,
+                    fim: inicio_seguinte,
+                    em_andamento: false,
+                    recurso: 0,
+                });
+            }
+        }
+        jornada.trechos.sort_by(|a, b| a.inicio.total_cmp(&b.inicio));
+    }
+    // Ordem estável entre quadros (a ordenação escolhida na tela parte desta)
+    jornadas.sort_by(|a, b| a.paciente.cmp(&b.paciente));
+    jornadas
+}
+
+/// Ordem da lista de pacientes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ordem {
+    Nome,
+    TempoTotal,
+    Espera,
+}
+
+/// Aba com a jornada de cada paciente: uma raia por paciente com as esperas em fila, o uso de
+/// cada instância de recurso e os intervalos ociosos, para entender por que alguém demorou.
+pub struct JornadaGUI {
+    rede: Arc<RedeHospitalar>,
+    filtro: String,
+    ordem: Ordem,
+}
+
+impl JornadaGUI {
+    pub fn new(rede: Arc<RedeHospitalar>) -> Self {
+        Self {
+            rede,
+            filtro: String::new(),
+            ordem: Ordem::TempoTotal,
+        }
+    }
+
+    pub fn desenhar(&mut self, ctx: &egui::Context) {
+        let agora = self.rede.hospitais[0].recursos.controle.agora().as_secs_f64();
+        let mut jornadas = montar_jornadas(&self.rede, agora);
+        let filtro = self.filtro.to_lowercase();
+        jornadas.retain(|j| j.paciente.to_lowercase().contains(&filtro));
+        match self.ordem {
+            Ordem::Nome => jornadas.sort_by(|a, b| a.paciente.cmp(&b.paciente)),
+            Ordem::TempoTotal => jornadas.sort_by(|a, b| (b.fim() - b.inicio()).total_cmp(&(a.fim() - a.inicio()))),
+            Ordem::Espera => jornadas.sort_by(|a, b| b.total(TipoTrecho::Espera).total_cmp(&a.total(TipoTrecho::Espera))),
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("🧭 Jornada dos Pacientes");
+            ui.horizontal(|ui| {
+                ui.label("Filtrar:");
+                ui.text_edit_singleline(&mut self.filtro);
+                ui.label("Ordenar por:");
+                ui.selectable_value(&mut self.ordem, Ordem::TempoTotal, "Tempo total");
+                ui.selectable_value(&mut self.ordem, Ordem::Espera, "Espera em fila");
+                ui.selectable_value(&mut self.ordem, Ordem::Nome, "Nome");
+            });
+            ui.separator();
+
+            if jornadas.is_empty() {
+                ui.label("Nenhum paciente com histórico ainda.");
+                return;
+            }
+
+            egui::ScrollArea::vertical().id_source("lista_jornadas").max_height(180.0).show(ui, |ui| {
+                egui::Grid::new("grid_jornadas").striped(true).show(ui, |ui| {
+                    for titulo in ["Paciente", "Total", "Em fila", "Em recurso", "Ocioso"] {
+                        ui.label(egui::RichText::new(titulo).strong());
+                    }
+                    ui.end_row();
+                    for jornada in &jornadas {
+                        ui.label(&jornada.paciente);
+                        ui.label(format!("{:.1}s", jornada.fim() - jornada.inicio()));
+                        ui.label(format!("{:.1}s", jornada.total(TipoTrecho::Espera)));
+                        ui.label(format!("{:.1}s", jornada.total(TipoTrecho::Uso)));
+                        ui.label(format!("{:.1}s", jornada.total(TipoTrecho::Ocioso)));
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.separator();
+
+            self.mostrar_raias(ui, &jornadas, agora);
+        });
+    }
+
+    /// Uma raia por paciente: faixas de espera em cima (cor clara) e faixas de uso embaixo (cor cheia,
+    /// com os intervalos ociosos em cinza). Trechos simultâneos ganham faixas próprias.
+    fn mostrar_raias(&self, ui: &mut egui::Ui, jornadas: &[Jornada], agora: f64) {
+        let mut graficos: Vec<BarChart> = vec![];
+        let mut rotulos: Vec<(f64, String)> = vec![];
+        let mut linha = 0.0;
+
+        for jornada in jornadas {
+            rotulos.push((linha, jornada.paciente.clone()));
+            let esperas: Vec<&Trecho> = jornada.trechos.iter().filter(|t| t.tipo == TipoTrecho::Espera).collect();
+            let usos: Vec<&Trecho> = jornada.trechos.iter().filter(|t| t.tipo != TipoTrecho::Espera).collect();
+            let (faixas_espera, total_espera) = distribuir_em_faixas(&esperas);
+            let (faixas_uso, total_uso) = distribuir_em_faixas(&usos);
+
+            let posicionados = esperas
+                .iter()
+                .zip(faixas_espera)
+                .map(|(trecho, faixa)| (*trecho, linha - 1.0 - faixa as f64))
+                .chain(usos.iter().zip(faixas_uso).map(|(trecho, faixa)| (*trecho, linha - 1.0 - (total_espera + faixa) as f64)));
+
+            for (trecho, y) in posicionados {
+                let base = CORES_RECURSOS[trecho.recurso % CORES_RECURSOS.len()];
+                let cor = match trecho.tipo {
+                    TipoTrecho::Espera => base.gamma_multiply(0.4),
+                    TipoTrecho::Uso => base,
+                    TipoTrecho::Ocioso => egui::Color32::DARK_GRAY,
+                };
+                let detalhes = format!(
+                    "{}\n{}\nInício: {:.1}s\nFim: {}\nDuração: {:.1}s",
+                    jornada.paciente,
+                    trecho.descricao,
+                    trecho.inicio,
+                    if trecho.em_andamento { "em andamento".to_string() } else { format!("{:.1}s", trecho.fim) },
+                    trecho.fim - trecho.inicio,
+                );
+                let barra = Bar::new(y, trecho.fim - trecho.inicio)
+                    .base_offset(trecho.inicio)
+                    .width(0.7)
+                    .name(detalhes)
+                    .fill(cor)
+                    .stroke(egui::Stroke::new(0.5, egui::Color32::BLACK));
+                graficos.push(
+                    BarChart::new(vec![barra])
+                        .horizontal()
+                        .element_formatter(Box::new(|barra, _| barra.name.clone())),
+                );
+            }
+            linha -= (1 + total_espera + total_uso) as f64 + 0.5;
+        }
+
+        Plot::new("raias_jornada")
+            .height(ui.available_height().max(300.0))
+            .include_x(0.0)
+            .include_x(agora)
+            .include_y(0.5)
+            .include_y(linha)
+            .label_formatter(|_, valor| format!("{:.1}s", valor.x))
+            .show_y(false)
+            .show_axes([true, false])
+            .show(ui, |plot_ui| {
+                for grafico in graficos {
+                    plot_ui.bar_chart(grafico);
+                }
+                // Nome do paciente preso à borda esquerda da área visível
+                let esquerda = plot_ui.plot_bounds().min()[0];
+                for (y, paciente) in rotulos {
+                    plot_ui.text(
+                        Text::new(PlotPoint::new(esquerda, y), egui::RichText::new(paciente).strong())
+                            .anchor(egui::Align2::LEFT_CENTER),
+                    );
+                }
+            });
+    }
+}
+
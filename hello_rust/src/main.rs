@@ -12,12 +12,37 @@ mod monitor_gui;
 // mod sincronizacao; // REMOVIDO: Arquivo obsoleto
 mod estatisticas;
 mod monitor;
-
-use paciente::Paciente;
-use recursos::{Recursos, HistoricoUso, EventoUso};
+mod clinica;
+mod deadlock;
+mod cenarios;
+mod rede;
+mod controle;
+mod simulacao;
+mod api_controle;
+mod http;
+mod metricas;
+mod painel_web;
+mod jornada;
+mod exportacao;
+mod relatorio;
+mod auditoria;
+mod cancelamento;
+#[cfg(test)]
+mod testes_simulacao;
+#[cfg(test)]
+mod testes_invariantes;
+
+use paciente::{Paciente, ModoAquisicao};
+use recursos::{Recursos, EventoUso, ConfigRecurso, PoliticaOrdem, TipoRecurso, MEDICO, SALA, LEITO, EXAME, ENFERMEIRO};
 use monitor_gui::{MonitorGUI, EstadoRecursosGUI};
 use estatisticas::Estatisticas;
 use monitor::iniciar_monitor;
+use auditoria::{iniciar_auditoria, ReacaoViolacao};
+use cenarios::{Cenario, Estrategia};
+use rede::{Hospital, RedeHospitalar, RegraTransferencia};
+use controle::ControleSimulacao;
+use simulacao::Simulacao;
+use jornada::JornadaGUI;
 
 use egui_plot::{Plot, BarChart, Bar, PlotPoint};
 
@@ -25,42 +50,91 @@ const ESCALA_TEMPO: f64 = 5.0;
 const QTD_MEDICOS: usize = 3;
 const QTD_SALAS: usize = 2;
 const QTD_LEITOS: usize = 4;
-// Constante para Exames, mantida aqui, mas não usada nas chamadas de construtor abaixo
 const QTD_EXAMES: usize = 4; 
+const QTD_ENFERMEIROS: usize = 2;
+// Como a equipe cirúrgica (Médico + Sala + Enfermeiro) é obtida: Incremental (hold-and-wait) ou Atomica
+const AQUISICAO_CIRURGIA: ModoAquisicao = ModoAquisicao::Atomica;
+// Prevenção de espera circular: pedidos fora da ordem global (campo `ordem`) são reportados ou rejeitados
+const POLITICA_ORDEM: PoliticaOrdem = PoliticaOrdem::Reportar;
+// Auditoria das invariantes dos recursos ao lado de cada monitor (`None` desliga);
+// com `Pausar` a simulação para na primeira violação, congelando o estado para inspeção
+const AUDITORIA: Option<ReacaoViolacao> = Some(ReacaoViolacao::Registrar);
+
+/// Tipos de recurso da simulação. Cada entrada ganha automaticamente reserva, filas,
+/// histórico, monitoramento e coluna na GUI; novos tipos são declarados apenas aqui.
+const CONFIG_RECURSOS: &[ConfigRecurso] = &[
+    ConfigRecurso { tipo: MEDICO, nome: "Médicos", instancia: "Médico", icone: "👨‍⚕️", capacidade: QTD_MEDICOS, ordem: 1 },
+    ConfigRecurso { tipo: SALA, nome: "Salas Cirurgia", instancia: "Sala", icone: "🏥", capacidade: QTD_SALAS, ordem: 3 },
+    ConfigRecurso { tipo: LEITO, nome: "Leitos", instancia: "Leito", icone: "🛏️", capacidade: QTD_LEITOS, ordem: 5 },
+    ConfigRecurso { tipo: EXAME, nome: "Exames", instancia: "Exame", icone: "🔬", capacidade: QTD_EXAMES, ordem: 2 },
+    ConfigRecurso { tipo: ENFERMEIRO, nome: "Enfermeiros", instancia: "Enfermeiro", icone: "👩‍⚕️", capacidade: QTD_ENFERMEIROS, ordem: 4 },
+];
+/// Rede regional da simulação padrão: o hospital principal usa `CONFIG_RECURSOS` e os vizinhos
+/// têm os mesmos tipos de recurso com outras capacidades.
+const HOSPITAL_PRINCIPAL: &str = "Hospital Central";
+const HOSPITAIS_VIZINHOS: &[(&str, &[(TipoRecurso, usize)])] = &[
+    ("Hospital Norte", &[(MEDICO, 1), (SALA, 1), (LEITO, 2), (EXAME, 1), (ENFERMEIRO, 1)]),
+    ("Hospital Sul", &[(MEDICO, 2), (SALA, 1), (LEITO, 3), (EXAME, 2), (ENFERMEIRO, 1)]),
+];
+// Sem o recurso livre na hora da etapa, o paciente é transferido (transporte em unidades de ESCALA_TEMPO)
+const REGRAS_TRANSFERENCIA: &[RegraTransferencia] = &[
+    RegraTransferencia { gatilho: SALA, transporte: 1.0 },
+    RegraTransferencia { gatilho: LEITO, transporte: 1.5 },
+];
+// Endereço local da API de controle (JSON por linha; ver `api_controle`)
+const ENDERECO_CONTROLE: &str = "127.0.0.1:7878";
+// Endereço local do endpoint de métricas do Prometheus (`/metrics`)
+const ENDERECO_METRICAS: &str = "127.0.0.1:9898";
+// Endereço local do painel web (página em `/`, estado por WebSocket em `/ws`)
+const ENDERECO_PAINEL: &str = "127.0.0.1:8080";
+// Pasta onde os gráficos do fim da execução são gravados (SVG e PNG)
+const PASTA_GRAFICOS: &str = "graficos";
+// Relatório HTML autocontido gravado no fim da execução
+const ARQUIVO_RELATORIO: &str = "relatorio_simulacao.html";
+// Paciência padrão dos pacientes não críticos (em unidades de ESCALA_TEMPO) e fila máxima tolerada
+const PACIENCIA_ESPERA: f64 = 4.0;
+const PACIENCIA_FILA_MAX: usize = 4;
 
 #[derive(Debug, Clone)]
 pub struct Snapshot {
     tempo: f64,
-    medicos: usize,
-    salas: usize,
-    leitos: usize,
-    // Nota: O campo 'exames' está faltando aqui, mantendo a estrutura original fornecida.
+    /// Permissões disponíveis de cada tipo de recurso, na ordem da configuração.
+    disponiveis: Vec<(TipoRecurso, usize)>,
+    /// Capacidade de cada tipo de recurso no momento (muda com a edição em execução).
+    capacidades: Vec<usize>,
 }
 
 // Estrutura para rastrear o histórico de recursos disponíveis
 #[derive(Debug)]
 pub struct HistoricoRecursos {
     snapshots: Vec<Snapshot>,
-    inicio: std::time::Instant,
+}
+
+impl Default for HistoricoRecursos {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl HistoricoRecursos {
     pub fn new() -> Self {
         Self {
             snapshots: vec![],
-            inicio: std::time::Instant::now(),
         }
     }
 
-    /// Registra o estado atual dos recursos no histórico.
+    /// Registra o estado atual dos recursos no histórico, no tempo simulado.
     /// `recursos` contém a contagem de semáforos disponíveis (atômico).
     pub fn registrar(&mut self, recursos: &Recursos) {
-        let t = self.inicio.elapsed().as_secs_f64();
+        let t = recursos.controle.agora().as_secs_f64();
         self.snapshots.push(Snapshot {
             tempo: (t * 100.0).round() / 100.0,
-            medicos: recursos.medicos.available_permits(),
-            salas: recursos.salas_cirurgia.available_permits(),
-            leitos: recursos.leitos.available_permits(),
+            disponiveis: recursos
+                .pools
+                .iter()
+                .map(|p| (p.config.tipo, p.semaforo.available_permits()))
+                .collect(),
+            capacidades: recursos.pools.iter().map(|p| p.capacidade()).collect(),
         });
     }
 }
@@ -70,9 +144,18 @@ async fn salvar_historico_csv(historico: &Arc<Mutex<HistoricoRecursos>>, filenam
     let historico_lock = historico.lock().await;
     let mut wtr = Writer::from_path(filename)?;
     
-    wtr.write_record(&["tempo", "medicos_disp", "salas_disp", "leitos_disp"])?;
+    // Cabeçalho gerado a partir dos tipos de recurso registrados
+    let mut cabecalho = vec!["tempo".to_string()];
+    if let Some(primeiro) = historico_lock.snapshots.first() {
+        cabecalho.extend(primeiro.disponiveis.iter().map(|(tipo, _)| format!("{}_disp", tipo)));
+        cabecalho.extend(primeiro.disponiveis.iter().map(|(tipo, _)| format!("{}_cap", tipo)));
+    }
+    wtr.write_record(&cabecalho)?;
     for snap in &historico_lock.snapshots {
-        wtr.serialize((snap.tempo, snap.medicos, snap.salas, snap.leitos))?;
+        let mut linha = vec![snap.tempo.to_string()];
+        linha.extend(snap.disponiveis.iter().map(|(_, disp)| disp.to_string()));
+        linha.extend(snap.capacidades.iter().map(|cap| cap.to_string()));
+        wtr.write_record(&linha)?;
     }
     wtr.flush()?;
     println!("✅ Histórico de recursos salvo em: {}", filename);
@@ -84,11 +167,9 @@ async fn salvar_logs_csv(logs: &Arc<Mutex<Vec<String>>>, filename: &str) -> Resu
     let logs_lock = logs.lock().await;
     let mut wtr = Writer::from_path(filename)?;
     
-    wtr.write_record(&["indice", "mensagem"])?;
-    let mut i = 1;
-    for log in logs_lock.iter() {
+    wtr.write_record(["indice", "mensagem"])?;
+    for (i, log) in (1..).zip(logs_lock.iter()) {
         wtr.serialize((i, log))?;
-        i += 1;
     }
     wtr.flush()?;
     println!("✅ Logs de simulação salvos em: {}", filename);
@@ -111,8 +192,8 @@ impl LogGUI {
     }
 }
 
-impl eframe::App for LogGUI {
-    fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
+impl LogGUI {
+    fn desenhar(&self, ctx: &egui::Context) {
         // Uso de try_lock() para acessar o tokio::sync::Mutex de forma não-bloqueante na thread da GUI.
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Logs de Execução em Tempo Real");
@@ -140,133 +221,199 @@ impl eframe::App for LogGUI {
                     }
                 });
         });
-        ctx.request_repaint();
     }
 }
 
 /// Estrutura de GUI para exibir gráficos e estatísticas da simulação.
 struct GraficoApp {
     historico_recursos: Arc<Mutex<HistoricoRecursos>>,
-    historico_uso: Arc<HistoricoUso>, // Contém Mutexes de Tokio internamente
+    recursos: Arc<Recursos>, // Pools (linhas do gráfico) e histórico de uso, com Mutexes de Tokio
     estatisticas: Arc<Estatisticas>, // Contém Mutexes de Std
+    /// Paciente destacado na linha do tempo (todas as barras dele em todos os recursos).
+    selecionado: Option<String>,
+}
+
+/// Cores das barras da linha do tempo; cada paciente fica sempre com a mesma cor.
+const CORES_PACIENTES: [egui::Color32; 8] = [
+    egui::Color32::from_rgb(46, 139, 87),
+    egui::Color32::from_rgb(255, 99, 71),
+    egui::Color32::from_rgb(60, 179, 113),
+    egui::Color32::from_rgb(255, 165, 0),
+    egui::Color32::from_rgb(0, 191, 255),
+    egui::Color32::from_rgb(147, 112, 219),
+    egui::Color32::from_rgb(240, 128, 128),
+    egui::Color32::from_rgb(218, 165, 32),
+];
+
+/// Uma barra da linha do tempo: o uso de uma instância de recurso por um paciente.
+struct BarraUso {
+    linha: f64,
+    recurso: String,
+    evento: EventoUso,
+    fim: f64,
+    em_uso: bool,
 }
 
 impl GraficoApp {
     fn new(
         historico_recursos: Arc<Mutex<HistoricoRecursos>>,
-        historico_uso: Arc<HistoricoUso>,
+        recursos: Arc<Recursos>,
         estatisticas: Arc<Estatisticas>,
     ) -> Self {
         Self {
             historico_recursos,
-            historico_uso,
+            recursos,
             estatisticas,
+            selecionado: None,
         }
     }
 
-    /// Desenha o gráfico de ocupação de recursos ao longo do tempo.
-    fn mostrar_grafico_ocupacao(&self, ui: &mut egui::Ui, max_time: f64) {
+    /// Cor fixa de um paciente, escolhida pela ordem em que ele aparece no histórico.
+    fn cor_paciente(pacientes: &[String], nome: &str) -> egui::Color32 {
+        let indice = pacientes.iter().position(|p| p == nome).unwrap_or(0);
+        CORES_PACIENTES[indice % CORES_PACIENTES.len()]
+    }
+
+    /// Desenha a linha do tempo de uso de todos os recursos (uma linha por instância), com
+    /// detalhes de cada uso ao passar o mouse e destaque das barras do paciente selecionado.
+    fn mostrar_grafico_ocupacao(&mut self, ui: &mut egui::Ui, max_time: f64) {
+        // Monta as linhas (cabeçalho do recurso + uma por instância) e as barras de cada uma.
         // Usamos .blocking_lock() na thread da GUI para acessar o tokio::sync::Mutex de HistoricoUso
-        let medico_eventos = self.historico_uso.medico.blocking_lock().clone();
-        let sala_eventos = self.historico_uso.sala.blocking_lock().clone();
-        let leito_eventos = self.historico_uso.leito.blocking_lock().clone();
+        let mut rotulos: Vec<(f64, String, bool)> = vec![];
+        let mut barras: Vec<BarraUso> = vec![];
+        let mut pacientes: Vec<String> = vec![];
+        let mut linha = 0.0;
+        for pool in &self.recursos.pools {
+            let eventos = self
+                .recursos
+                .historico_uso
+                .eventos(pool.config.tipo)
+                .map(|eventos| eventos.blocking_lock().clone())
+                .unwrap_or_default();
+            let instancias = eventos.iter().map(|e| e.instancia_id).max().unwrap_or(0).max(pool.capacidade());
+
+            rotulos.push((linha, pool.config.titulo(), true));
+            for instancia in 1..=instancias {
+                rotulos.push((linha - instancia as f64, format!("{} {}", pool.config.instancia, instancia), false));
+            }
+            for evento in eventos {
+                if !pacientes.contains(&evento.nome_paciente) {
+                    pacientes.push(evento.nome_paciente.clone());
+                }
+                let em_uso = evento.fim == 0.0;
+                barras.push(BarraUso {
+                    linha: linha - evento.instancia_id as f64,
+                    recurso: format!("{} {}", pool.config.instancia, evento.instancia_id),
+                    fim: if em_uso { max_time } else { evento.fim },
+                    em_uso,
+                    evento,
+                });
+            }
+            linha -= instancias as f64 + 1.5;
+        }
+
+        // Seleção do paciente a destacar (também por clique numa barra)
+        ui.horizontal(|ui| {
+            ui.label("Destacar paciente:");
+            egui::ComboBox::from_id_source("paciente_destacado")
+                .selected_text(self.selecionado.as_deref().unwrap_or("Nenhum"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.selecionado, None, "Nenhum");
+                    let mut ordenados = pacientes.clone();
+                    ordenados.sort();
+                    for nome in ordenados {
+                        let rotulo = nome.clone();
+                        ui.selectable_value(&mut self.selecionado, Some(nome), rotulo);
+                    }
+                });
+            ui.label(egui::RichText::new("(ou clique numa barra; clique no vazio para limpar)").weak());
+        });
+
+        let selecionado = self.selecionado.clone();
+        let graficos: Vec<BarChart> = barras
+            .iter()
+            .map(|barra| {
+                let evento = &barra.evento;
+                let destacado = selecionado.as_deref() == Some(evento.nome_paciente.as_str());
+                let mut cor = Self::cor_paciente(&pacientes, &evento.nome_paciente);
+                let mut contorno = egui::Stroke::new(1.0, egui::Color32::BLACK);
+                if destacado {
+                    contorno = egui::Stroke::new(2.5, egui::Color32::WHITE);
+                } else if selecionado.is_some() {
+                    cor = cor.gamma_multiply(0.25);
+                }
 
-        let plot = Plot::new("timeline_recursos")
+                let detalhes = format!(
+                    "Paciente: {}\nEtapa: {}\nRecurso: {}\nInício: {:.1}s\nFim: {}\nDuração: {:.1}s",
+                    evento.nome_paciente,
+                    evento.etapa,
+                    barra.recurso,
+                    evento.inicio,
+                    if barra.em_uso { "em uso".to_string() } else { format!("{:.1}s", barra.fim) },
+                    barra.fim - evento.inicio,
+                );
+                let bar = Bar::new(barra.linha, barra.fim - evento.inicio)
+                    .base_offset(evento.inicio)
+                    .width(0.7)
+                    .name(detalhes)
+                    .fill(cor)
+                    .stroke(contorno);
+                BarChart::new(vec![bar])
+                    .horizontal()
+                    .element_formatter(Box::new(|bar, _| bar.name.clone()))
+            })
+            .collect();
+
+        let altura = (rotulos.len() as f32 * 22.0).max(300.0);
+        let clicado = Plot::new("timeline_recursos")
             .width(ui.available_width())
-            .height(500.0)
+            .height(altura)
             .include_x(0.0)
             .include_x(max_time * 1.05)
+            .include_y(1.0)
+            .include_y(linha)
             .label_formatter(|_, val| format!("{:.1}s", val.x))
             .allow_zoom(true)
             .allow_drag(true)
-            .center_y_axis(false)
-            .show_x(true)
-            .show_y(false);
-
-        plot.show(ui, |plot_ui| {
-            let cores = [
-                egui::Color32::from_rgb(46, 139, 87),
-                egui::Color32::from_rgb(255, 99, 71),
-                egui::Color32::from_rgb(60, 179, 113),
-                egui::Color32::from_rgb(255, 165, 0),
-                egui::Color32::from_rgb(0, 191, 255),
-                egui::Color32::from_rgb(147, 112, 219),
-                egui::Color32::from_rgb(240, 128, 128),
-                egui::Color32::from_rgb(240, 128, 128),
-            ];
-            let mut cor_idx = 0;
-            let mut y_offset = 0.0;
-            let bar_height = 0.4;
-
-            let mut plotar_eventos = |current_y_offset: f64, cor_idx_ref: &mut usize, nome_recurso: &str, eventos: &Vec<EventoUso>| -> f64 {
-                if eventos.is_empty() {
-                    return 0.0;
+            .show_y(false)
+            .show_axes([true, false])
+            .show(ui, |plot_ui| {
+                for grafico in graficos {
+                    plot_ui.bar_chart(grafico);
                 }
 
-                plot_ui.text(
-                    egui_plot::Text::new(
-                        PlotPoint::new(max_time * 0.05, current_y_offset + 0.5),
-                        nome_recurso,
-                    )
-                    .anchor(egui::Align2::LEFT_CENTER)
-                    .color(egui::Color32::BLACK),
-                );
-
-                let max_id = eventos.iter().map(|e| e.instancia_id).max().unwrap_or(1);
-                let mut bars = Vec::new();
-
-                for evento in eventos {
-                    let duracao = if evento.fim == 0.0 {
-                        max_time - evento.inicio
-                    } else {
-                        evento.fim - evento.inicio
-                    };
-                    let centro_x = evento.inicio + duracao / 2.0;
-                    let y = current_y_offset + evento.instancia_id as f64 * 0.5;
-
-                    let bar = Bar::new(centro_x, y)
-                        .width(duracao)
-                        .name(format!(
-                            "{}: {}",
-                            nome_recurso.split(" ").next().unwrap_or(""),
-                            evento.nome_paciente.clone()
-                        ))
-                        .fill(cores[*cor_idx_ref % cores.len()])
-                        .stroke(egui::Stroke::new(1.0, egui::Color32::BLACK));
-
-                    bars.push(bar);
-                    *cor_idx_ref += 1;
-
+                // Rótulos presos à borda esquerda da área visível, acompanhando zoom e arraste
+                let esquerda = plot_ui.plot_bounds().min()[0];
+                for (y, texto, cabecalho) in &rotulos {
+                    let mut rotulo = egui::RichText::new(texto);
+                    rotulo = if *cabecalho { rotulo.strong() } else { rotulo.color(egui::Color32::GRAY) };
                     plot_ui.text(
-                        egui_plot::Text::new(
-                            PlotPoint::new(0.0, y),
-                            format!("{}", evento.instancia_id),
-                        )
-                        .anchor(egui::Align2::LEFT_CENTER)
-                        .color(egui::Color32::DARK_GRAY),
+                        egui_plot::Text::new(PlotPoint::new(esquerda, *y), rotulo).anchor(egui::Align2::LEFT_CENTER),
                     );
                 }
 
-                plot_ui.bar_chart(BarChart::new(bars).width(bar_height));
-                (max_id as f64 * 0.5) + 0.5
-            };
-
-            y_offset += plotar_eventos(y_offset, &mut cor_idx, "MÉDICOS", &medico_eventos);
-            y_offset += 0.5;
-            y_offset += plotar_eventos(y_offset, &mut cor_idx, "SALAS", &sala_eventos);
-            y_offset += 0.5;
-            y_offset += plotar_eventos(y_offset, &mut cor_idx, "LEITOS", &leito_eventos);
+                // Barra sob o ponteiro no momento do clique
+                if !plot_ui.response().clicked() {
+                    return None;
+                }
+                let ponteiro = plot_ui.pointer_coordinate()?;
+                Some(
+                    barras
+                        .iter()
+                        .find(|b| (ponteiro.y - b.linha).abs() <= 0.35 && (b.evento.inicio..=b.fim).contains(&ponteiro.x))
+                        .map(|b| b.evento.nome_paciente.clone()),
+                )
+            })
+            .inner;
 
-            plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
-                [0.0, -1.0],
-                [max_time * 1.05, y_offset + 1.0],
-            ));
-        });
+        if let Some(paciente) = clicado {
+            self.selecionado = paciente;
+        }
     }
 }
 
-impl eframe::App for GraficoApp {
-    fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
+impl GraficoApp {
+    fn desenhar(&mut self, ctx: &egui::Context) {
         let mut max_time = 0.0;
         
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -298,6 +445,21 @@ impl eframe::App for GraficoApp {
                 ui.label(format!("Tempo médio de atendimento: {:.2}s", media));
                 drop(lock);
 
+                let desistencias = self.estatisticas.desistencias.lock().unwrap().len();
+                let abortados = self.estatisticas.abortados.lock().unwrap().len();
+                let cancelados = self.estatisticas.cancelamentos.lock().unwrap().len();
+                ui.label(format!("Pacientes que desistiram: {} | Abortados: {} | Cancelados: {}", desistencias, abortados, cancelados));
+
+                let taxas = self.estatisticas.taxas_desfecho();
+                ui.label(format!(
+                    "Desfechos ({} tratados): Bom {:.0}% | Complicação {:.0}% | Óbito {:.0}% | Dentro do prazo {:.0}%",
+                    taxas.tratados,
+                    taxas.bom * 100.0,
+                    taxas.complicacao * 100.0,
+                    taxas.obito * 100.0,
+                    taxas.dentro_do_prazo * 100.0
+                ));
+
                 ui.separator();
 
                 // Apenas mostra o gráfico se tiver um tempo base (max_time > 0.0)
@@ -309,94 +471,263 @@ impl eframe::App for GraficoApp {
                 }
             });
         });
-        ctx.request_repaint();
     }
 }
 
+/// Abas da janela principal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aba {
+    Monitor,
+    Logs,
+    Graficos,
+    Jornada,
+}
+
+/// Janela única da aplicação: monitor, logs, gráficos e jornadas em abas, sobre os mesmos dados compartilhados.
+struct AplicacaoSimulador {
+    aba: Aba,
+    monitor: MonitorGUI,
+    logs: LogGUI,
+    graficos: GraficoApp,
+    jornada: JornadaGUI,
+}
+
+impl eframe::App for AplicacaoSimulador {
+    fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
+        // O monitor acompanha a simulação mesmo quando a aba dele não está visível
+        self.monitor.atualizar();
+
+        egui::TopBottomPanel::top("abas").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.aba, Aba::Monitor, "🩺 Monitor de Recursos");
+                ui.selectable_value(&mut self.aba, Aba::Logs, "📝 Logs de Execução");
+                ui.selectable_value(&mut self.aba, Aba::Graficos, "📊 Estatísticas e Gráfico de Uso");
+                ui.selectable_value(&mut self.aba, Aba::Jornada, "🧭 Jornada dos Pacientes");
+            });
+        });
+
+        match self.aba {
+            Aba::Monitor => self.monitor.desenhar(ctx),
+            Aba::Logs => self.logs.desenhar(ctx),
+            Aba::Graficos => self.graficos.desenhar(ctx),
+            Aba::Jornada => self.jornada.desenhar(ctx),
+        }
+
+        // Solicita repintura contínua para atualizar o estado e o efeito de piscar
+        ctx.request_repaint_after(Duration::from_millis(50));
+    }
+}
+
+/// Configuração de um hospital vizinho: a configuração principal com as capacidades informadas.
+fn config_vizinho(capacidades: &[(TipoRecurso, usize)]) -> Vec<ConfigRecurso> {
+    CONFIG_RECURSOS
+        .iter()
+        .map(|config| ConfigRecurso {
+            capacidade: capacidades
+                .iter()
+                .find(|(tipo, _)| *tipo == config.tipo)
+                .map_or(config.capacidade, |(_, qtd)| *qtd),
+            ..*config
+        })
+        .collect()
+}
+
+/// Pacientes da simulação padrão (sem cenário).
+fn pacientes_padrao() -> Vec<Paciente> {
+    // Paciência em segundos simulados, já aplicada a escala de tempo
+    let paciencia = |fator: f64| Duration::from_secs_f64(PACIENCIA_ESPERA * fator * ESCALA_TEMPO);
+
+    vec![
+        Paciente::novo_com_prioridade("P01-Critico", 45, "Infarto", true, 1),
+        Paciente::novo_com_prioridade("P02-Normal", 22, "Fratura", false, 0)
+            .com_paciencia(paciencia(1.5), PACIENCIA_FILA_MAX),
+        Paciente::novo_com_prioridade("P03-Normal", 70, "Apendicite", true, 0)
+            .com_paciencia(paciencia(2.0), PACIENCIA_FILA_MAX),
+        Paciente::novo_com_prioridade("P04-Critico", 30, "AVC", false, 1),
+        Paciente::novo_com_prioridade("P05-Normal", 55, "Gripe Forte", false, 0)
+            .com_paciencia(paciencia(1.0), PACIENCIA_FILA_MAX),
+        Paciente::novo_com_prioridade("P06-Normal", 18, "Corte", false, 0)
+            .com_paciencia(paciencia(0.5), PACIENCIA_FILA_MAX - 2),
+        Paciente::novo_com_prioridade("P07-Critico", 60, "Politraum", true, 1),
+        Paciente::novo_com_prioridade("P08-Normal", 35, "Dor", false, 0)
+            .com_paciencia(paciencia(1.0), PACIENCIA_FILA_MAX),
+    ]
+    .into_iter()
+    .map(|paciente| paciente.com_aquisicao_cirurgia(AQUISICAO_CIRURGIA))
+    .collect()
+}
+
+/// Lê `<cenario> [estrategia]` dos argumentos. Sem argumentos roda a simulação padrão;
+/// nomes desconhecidos encerram o programa listando o catálogo.
+fn cenario_da_linha_de_comando() -> Option<Cenario> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let nome = args.first()?;
+
+    let estrategia = match args.get(1).map(|e| e.parse::<Estrategia>()) {
+        Some(Ok(estrategia)) => estrategia,
+        Some(Err(erro)) => {
+            eprintln!("❌ {}", erro);
+            std::process::exit(2);
+        }
+        None => Estrategia::default(),
+    };
+
+    cenarios::montar(nome, estrategia).or_else(|| {
+        eprintln!("❌ Cenário '{}' desconhecido. Disponíveis:", nome);
+        for (nome, descricao) in cenarios::CATALOGO {
+            eprintln!("   {:<16} {}", nome, descricao);
+        }
+        std::process::exit(2);
+    })
+}
+
 /// Função principal que inicializa o runtime do Tokio, o estado compartilhado e as GUIs Eframe.
 fn main() -> eframe::Result<()> {
     // 1. Inicializa o runtime do Tokio
     let rt = Runtime::new().unwrap();
 
-    // 2. Inicializa recursos e estados compartilhados usando Arc<tokio::sync::Mutex<...>>
-    let recursos = Arc::new(Recursos::novo(QTD_MEDICOS, QTD_SALAS, QTD_LEITOS, QTD_EXAMES));
+    // 2. Escolhe entre a simulação padrão e um cenário do catálogo (`cargo run -- <cenario> [estrategia]`)
+    let cenario = cenario_da_linha_de_comando();
+    let em_cenario = cenario.is_some();
+    // Um único controle de pausa/retomada para todos os hospitais
+    let controle = Arc::new(ControleSimulacao::novo());
+    // Parâmetros que abrem o relatório HTML do fim da execução
+    let mut parametros = vec![("Escala de tempo".to_string(), format!("{} s por unidade de tempo", ESCALA_TEMPO))];
+    let (config_recursos, pacientes_simulacao, recursos) = match cenario {
+        Some(cenario) => {
+            println!("🧪 Cenário '{}' ({}): {}", cenario.nome, cenario.estrategia, cenario.descricao);
+            parametros.push(("Cenário".to_string(), format!("{}: {}", cenario.nome, cenario.descricao)));
+            parametros.push(("Estratégia contra deadlock".to_string(), cenario.estrategia.to_string()));
+            let recursos = cenario.estrategia.configurar(Recursos::novo(&cenario.recursos));
+            (cenario.recursos, cenario.pacientes, recursos)
+        }
+        None => {
+            parametros.push(("Cenário".to_string(), "simulação padrão".to_string()));
+            parametros.push(("Aquisição da equipe cirúrgica".to_string(), format!("{:?}", AQUISICAO_CIRURGIA)));
+            parametros.push(("Política de ordem global".to_string(), format!("{:?}", POLITICA_ORDEM)));
+            parametros.push((
+                "Paciência dos não críticos".to_string(),
+                format!("{} unidades de tempo, fila máxima {}", PACIENCIA_ESPERA, PACIENCIA_FILA_MAX),
+            ));
+            (
+                CONFIG_RECURSOS.to_vec(),
+                pacientes_padrao(),
+                Recursos::novo(CONFIG_RECURSOS).com_politica_ordem(POLITICA_ORDEM),
+            )
+        }
+    };
+    parametros.push(("Pacientes iniciais".to_string(), pacientes_simulacao.len().to_string()));
+    let recursos = recursos.com_controle(controle.clone());
+
+    // 3. Inicializa recursos e estados compartilhados usando Arc<tokio::sync::Mutex<...>>
+    let recursos = Arc::new(recursos);
     
     // Todos os dados acessados em tarefas assíncronas usam tokio::sync::Mutex
-    let estado_gui = Arc::new(Mutex::new(EstadoRecursosGUI::new(QTD_MEDICOS, QTD_SALAS, QTD_LEITOS, QTD_EXAMES)));
+    let estado_gui = Arc::new(Mutex::new(EstadoRecursosGUI::new(&config_recursos)));
     let historico = Arc::new(Mutex::new(HistoricoRecursos::new()));
     let estatisticas = Arc::new(Estatisticas::novo());
     let logs = Arc::new(Mutex::new(vec![]));
 
-    let pacientes_simulacao = vec![
-        Paciente::novo_com_prioridade("P01-Critico", 45, "Infarto", true, 1),
-        Paciente::novo_com_prioridade("P02-Normal", 22, "Fratura", false, 0),
-        Paciente::novo_com_prioridade("P03-Normal", 70, "Apendicite", true, 0),
-        Paciente::novo_com_prioridade("P04-Critico", 30, "AVC", false, 1),
-        Paciente::novo_com_prioridade("P05-Normal", 55, "Gripe Forte", false, 0),
-        Paciente::novo_com_prioridade("P06-Normal", 18, "Corte", false, 0),
-        Paciente::novo_com_prioridade("P07-Critico", 60, "Politraum", true, 1),
-        Paciente::novo_com_prioridade("P08-Normal", 35, "Dor", false, 0),
-    ];
+    // Rede regional: o hospital principal e, na simulação padrão, os vizinhos que recebem transferências
+    let mut hospitais = vec![Hospital::novo(HOSPITAL_PRINCIPAL, recursos.clone(), estado_gui.clone())];
+    let mut regras: &[RegraTransferencia] = &[];
+    if !em_cenario {
+        for (nome, capacidades) in HOSPITAIS_VIZINHOS {
+            let config = config_vizinho(capacidades);
+            hospitais.push(Hospital::novo(
+                nome,
+                Arc::new(Recursos::novo(&config).com_politica_ordem(POLITICA_ORDEM).com_controle(controle.clone())),
+                Arc::new(Mutex::new(EstadoRecursosGUI::new(&config))),
+            ));
+        }
+        regras = REGRAS_TRANSFERENCIA;
+    }
+    let rede = Arc::new(regras.iter().fold(RedeHospitalar::nova(hospitais), |rede, regra| rede.com_regra(*regra)));
+    let simulacao = Arc::new(Simulacao::nova(
+        rede.clone(),
+        estatisticas.clone(),
+        logs.clone(),
+        controle,
+        ESCALA_TEMPO,
+        rt.handle().clone(),
+    ));
 
     let mut handles = vec![];
 
-    // 3. Spawna a tarefa do Monitor
+    // Os hospitais vizinhos têm monitor próprio (deadlock e estado da GUI), com histórico e logs locais
+    for hospital in rede.hospitais.iter().skip(1) {
+        let logs_locais = Arc::new(Mutex::new(vec![]));
+        handles.push(rt.spawn(iniciar_monitor(
+            hospital.recursos.clone(),
+            hospital.estado.clone(),
+            Arc::new(Mutex::new(HistoricoRecursos::new())),
+            logs_locais.clone(),
+        )));
+        if let Some(reacao) = AUDITORIA {
+            handles.push(rt.spawn(iniciar_auditoria(hospital.recursos.clone(), hospital.estado.clone(), logs_locais, reacao)));
+        }
+    }
+
+    // 4. Spawna a tarefa do Monitor
     handles.push(rt.spawn(iniciar_monitor(
         recursos.clone(),
         estado_gui.clone(),
         historico.clone(),
         logs.clone(),
     )));
+    if let Some(reacao) = AUDITORIA {
+        handles.push(rt.spawn(iniciar_auditoria(recursos.clone(), estado_gui.clone(), logs.clone(), reacao)));
+        parametros.push(("Auditoria de invariantes".to_string(), format!("{:?}", reacao)));
+    }
 
-    let recursos_clone = recursos.clone();
-    let estado_clone = estado_gui.clone();
+    // API de controle (consulta, injeção de pacientes, pausa, capacidade, preempção)
+    let simulacao_api = simulacao.clone();
+    handles.push(rt.spawn(async move {
+        if let Err(e) = api_controle::iniciar_servidor_controle(simulacao_api, ENDERECO_CONTROLE).await {
+            eprintln!("❌ API de controle indisponível em {}: {}", ENDERECO_CONTROLE, e);
+        }
+    }));
+
+    // Métricas para o Prometheus
+    let simulacao_metricas = simulacao.clone();
+    handles.push(rt.spawn(async move {
+        if let Err(e) = metricas::iniciar_servidor_metricas(simulacao_metricas, ENDERECO_METRICAS).await {
+            eprintln!("❌ Endpoint de métricas indisponível em {}: {}", ENDERECO_METRICAS, e);
+        }
+    }));
+
+    // Painel web para quem não roda a janela nativa
+    let simulacao_painel = simulacao.clone();
+    handles.push(rt.spawn(async move {
+        if let Err(e) = painel_web::iniciar_painel_web(simulacao_painel, ENDERECO_PAINEL).await {
+            eprintln!("❌ Painel web indisponível em {}: {}", ENDERECO_PAINEL, e);
+        }
+    }));
+
+    let rede_clone = rede.clone();
     let logs_clone = logs.clone();
     let historico_clone = historico.clone();
     let estatisticas_pac = estatisticas.clone();
+    let simulacao_pac = simulacao.clone();
+    let recursos_pac = recursos.clone();
 
-    // 4. Spawna a tarefa de simulação dos pacientes
+    // 5. Spawna a tarefa de simulação dos pacientes
     handles.push(rt.spawn(async move {
         registrar_log(&logs_clone, "Sistema iniciado. Iniciando atendimento...").await;
         
-        // Spawna uma tarefa para cada paciente
-        let handles: Vec<_> = pacientes_simulacao
-            .into_iter()
-            .map(|paciente| {
-                let recursos_pac = recursos_clone.clone();
-                let estado_pac = estado_clone.clone();
-                let logs_clone_pac = logs_clone.clone();
-                let estatisticas_pac_clone = estatisticas_pac.clone();
-
-                tokio::spawn(async move {
-                    registrar_log(
-                        &logs_clone_pac,
-                        &format!("🔹 Paciente {} iniciou o processo de atendimento.", paciente.nome),
-                    ).await;
-                    
-                    let inicio = estatisticas_pac_clone.iniciar_atendimento(&paciente.nome);
-                    
-                    // Onde a simulação do paciente acontece
-                    let concluido_sucesso = paciente.atender_com_escala(recursos_pac, estado_pac, ESCALA_TEMPO).await;
-                    
-                    estatisticas_pac_clone.finalizar_atendimento(&paciente.nome, inicio, concluido_sucesso); 
-                    
-                    if concluido_sucesso {
-                        registrar_log(
-                            &logs_clone_pac,
-                            &format!("✅ Concluído atendimento: {}", paciente.nome),
-                        ).await;
-                    }
-                })
-            })
-            .collect();
-
-        // Aguarda a conclusão de todos os pacientes
-        for handle in handles {
-            let _ = handle.await;
+        // Admite cada paciente no hospital principal (uma tarefa por paciente)
+        for paciente in pacientes_simulacao {
+            if let Err(e) = simulacao_pac.admitir(paciente, 0) {
+                registrar_log(&logs_clone, &format!("❌ Paciente não admitido: {}", e)).await;
+            }
         }
 
+        // Aguarda a conclusão de todos os pacientes (inclusive os injetados pela API)
+        simulacao_pac.aguardar_pacientes().await;
+
         // Gera o relatório final
         estatisticas_pac.imprimir_relatorio(); 
+        rede_clone.imprimir_transferencias().await;
 
         // Dá um pequeno tempo para o monitor registrar o último estado
         sleep(Duration::from_secs_f64(ESCALA_TEMPO * 0.5)).await;
@@ -406,9 +737,15 @@ fn main() -> eframe::Result<()> {
         // Salva os dados
         let _ = salvar_historico_csv(&historico_clone, "historico_recursos.csv").await;
         let _ = salvar_logs_csv(&logs_clone, "logs_simulacao.csv").await;
+        if let Err(e) = exportacao::exportar_graficos(&recursos_pac, &historico_clone, PASTA_GRAFICOS).await {
+            eprintln!("❌ Gráficos não exportados: {}", e);
+        }
+        if let Err(e) = relatorio::salvar_relatorio(&simulacao_pac, &historico_clone, &parametros, ARQUIVO_RELATORIO).await {
+            eprintln!("❌ Relatório HTML não gerado: {}", e);
+        }
     }));
 
-    // 5. Roda as tarefas assíncronas do Tokio em uma thread dedicada
+    // 6. Roda as tarefas assíncronas do Tokio em uma thread dedicada
     std::thread::spawn(move || {
         rt.block_on(async {
             for handle in handles {
@@ -417,54 +754,25 @@ fn main() -> eframe::Result<()> {
         });
     });
 
-    // 6. Roda as GUIs Eframe em threads separadas, incluindo a thread principal
-
-    // MonitorGUI (na thread principal)
-    let monitor_app = MonitorGUI::new(estado_gui.clone(), recursos.clone());
-    let options_monitor = eframe::NativeOptions {
-        viewport: ViewportBuilder::default().with_inner_size(egui::vec2(650.0, 500.0)),
+    // 7. Roda a janela da aplicação (monitor, logs, gráficos e jornadas em abas) na thread principal
+    let aplicacao = AplicacaoSimulador {
+        aba: Aba::Monitor,
+        monitor: MonitorGUI::new(estado_gui.clone(), recursos.clone())
+            .com_rede(rede.clone())
+            .com_simulacao(simulacao.clone())
+            // O passo único avança uma unidade de tempo
+            .com_passo_unico(Duration::from_secs_f64(ESCALA_TEMPO)),
+        logs: LogGUI::new(logs.clone()),
+        graficos: GraficoApp::new(historico.clone(), recursos.clone(), estatisticas.clone()),
+        jornada: JornadaGUI::new(rede.clone()),
+    };
+    let options = eframe::NativeOptions {
+        viewport: ViewportBuilder::default().with_inner_size(egui::vec2(1000.0, 700.0)),
         ..Default::default()
     };
     eframe::run_native(
-        "Monitor de Recursos (Slots)",
-        options_monitor,
-        Box::new(|_cc| Ok(Box::new(monitor_app))),
-    ).expect("Falha ao rodar o MonitorGUI");
-
-
-    // LogGUI (em thread separada)
-    let logs_clone_gui = logs.clone();
-    std::thread::spawn(move || {
-        let logs_app = LogGUI::new(logs_clone_gui);
-        let options = eframe::NativeOptions {
-            viewport: ViewportBuilder::default().with_inner_size(egui::vec2(600.0, 400.0)),
-            ..Default::default()
-        };
-        let _ = eframe::run_native(
-            "Logs de Execução",
-            options,
-            Box::new(|_cc| Ok(Box::new(logs_app))),
-        );
-    });
-
-    // GraficoApp (em thread separada)
-    let historico_graf = historico.clone();
-    let historico_uso_graf = recursos.historico_uso.clone();
-    let estatisticas_graf = estatisticas.clone();
-    std::thread::spawn(move || {
-        let graf_app = GraficoApp::new(historico_graf, historico_uso_graf, estatisticas_graf);
-        let options = eframe::NativeOptions {
-            viewport: ViewportBuilder::default().with_inner_size(egui::vec2(1000.0, 700.0)),
-            ..Default::default()
-        };
-        let _ = eframe::run_native(
-            "Estatísticas e Gráfico de Uso",
-            options,
-            Box::new(|_cc| Ok(Box::new(graf_app))),
-        );
-    });
-
-    // O retorno Ok(()) é necessário, mas o eframe::run_native acima já bloqueia.
-    // Retornamos Ok(()) para satisfazer a assinatura.
-    Ok(())
+        "Simulador Hospitalar",
+        options,
+        Box::new(|_cc| Ok(Box::new(aplicacao))),
+    )
 }
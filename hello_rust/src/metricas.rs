@@ -0,0 +1,156 @@
+// metricas.rs
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tokio::net::{TcpListener, TcpStream};
+use crate::http;
+use crate::simulacao::Simulacao;
+
+/// Limites (em segundos) dos buckets dos histogramas de tempo.
+const BUCKETS_SEGUNDOS: [f64; 9] = [0.5, 1.0, 2.5, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0];
+
+// ---------------- Formato de texto do Prometheus ----------------
+
+/// Acumula as famílias de métricas no formato de exposição de texto (versão 0.0.4).
+#[derive(Default)]
+struct Exposicao {
+    texto: String,
+}
+
+impl Exposicao {
+    fn familia(&mut self, nome: &str, tipo: &str, ajuda: &str) {
+        let _ = writeln!(self.texto, "# HELP {} {}", nome, ajuda);
+        let _ = writeln!(self.texto, "# TYPE {} {}", nome, tipo);
+    }
+
+    fn amostra(&mut self, nome: &str, rotulos: &[(&str, &str)], valor: f64) {
+        let _ = writeln!(self.texto, "{}{} {}", nome, formatar_rotulos(rotulos), valor);
+    }
+
+    /// Série de histograma (buckets cumulativos, `_sum` e `_count`) a partir das observações.
+    fn histograma(&mut self, nome: &str, rotulos: &[(&str, &str)], observacoes: &[f64]) {
+        for limite in BUCKETS_SEGUNDOS {
+            let le = limite.to_string();
+            let mut com_le = rotulos.to_vec();
+            com_le.push(("le", &le));
+            let contagem = observacoes.iter().filter(|v| **v <= limite).count();
+            self.amostra(&format!("{}_bucket", nome), &com_le, contagem as f64);
+        }
+        let mut com_le = rotulos.to_vec();
+        com_le.push(("le", "+Inf"));
+        self.amostra(&format!("{}_bucket", nome), &com_le, observacoes.len() as f64);
+        self.amostra(&format!("{}_sum", nome), rotulos, observacoes.iter().sum());
+        self.amostra(&format!("{}_count", nome), rotulos, observacoes.len() as f64);
+    }
+}
+
+fn formatar_rotulos(rotulos: &[(&str, &str)]) -> String {
+    if rotulos.is_empty() {
+        return String::new();
+    }
+    let pares: Vec<String> = rotulos
+        .iter()
+        .map(|(nome, valor)| {
+            let valor = valor.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            format!("{}=\"{}\"", nome, valor)
+        })
+        .collect();
+    format!("{{{}}}", pares.join(","))
+}
+
+// ---------------- Métricas da Simulação ----------------
+
+/// Gera o texto de `/metrics` com o estado atual de todos os hospitais da rede.
+pub async fn renderizar_metricas(simulacao: &Simulacao) -> String {
+    let hospitais = &simulacao.rede.hospitais;
+    let mut m = Exposicao::default();
+
+    // Gauges por hospital e tipo de recurso, lidos direto dos pools
+    let gauges_pool = [
+        ("simulador_recurso_disponiveis", "Instâncias livres (permissões disponíveis) por tipo de recurso."),
+        ("simulador_recurso_capacidade", "Capacidade atual por tipo de recurso."),
+        ("simulador_fila_tamanho", "Pacientes na fila de espera de cada tipo de recurso."),
+        ("simulador_deadlock_tamanho", "Pacientes na lista de espera longa (suspeitos de deadlock) de cada recurso."),
+    ];
+    for (indice, (nome, ajuda)) in gauges_pool.iter().enumerate() {
+        m.familia(nome, "gauge", ajuda);
+        for h in hospitais {
+            for pool in &h.recursos.pools {
+                let valor = match indice {
+                    0 => pool.semaforo.available_permits(),
+                    1 => pool.capacidade(),
+                    2 => pool.fila.lock().await.len(),
+                    _ => pool.deadlock.lock().await.len(),
+                };
+                m.amostra(nome, &[("hospital", h.nome), ("recurso", pool.config.tipo)], valor as f64);
+            }
+        }
+    }
+
+    m.familia("simulador_pacientes_em_atendimento", "gauge", "Pacientes com atendimento em andamento no hospital.");
+    for h in hospitais {
+        let em_andamento = h.estado.lock().await.progresso_pacientes.len();
+        m.amostra("simulador_pacientes_em_atendimento", &[("hospital", h.nome)], em_andamento as f64);
+    }
+
+    m.familia("simulador_pausado", "gauge", "1 se a simulação está pausada.");
+    m.amostra("simulador_pausado", &[], if simulacao.controle.pausado() { 1.0 } else { 0.0 });
+
+    // Contadores de desfecho do atendimento (globais: o paciente pode passar por vários hospitais)
+    let estatisticas = &simulacao.estatisticas;
+    let contadores = [
+        ("simulador_pacientes_concluidos_total", "Atendimentos concluídos.", estatisticas.atendimentos.lock().unwrap().len()),
+        ("simulador_pacientes_abortados_total", "Atendimentos abortados (falha de reserva).", estatisticas.abortados.lock().unwrap().len()),
+        ("simulador_pacientes_cancelados_total", "Atendimentos cancelados (operador, preempção ou prazo).", estatisticas.cancelamentos.lock().unwrap().len()),
+        ("simulador_pacientes_desistentes_total", "Pacientes que desistiram sem atendimento.", estatisticas.desistencias.lock().unwrap().len()),
+    ];
+    for (nome, ajuda, valor) in contadores {
+        m.familia(nome, "counter", ajuda);
+        m.amostra(nome, &[], valor as f64);
+    }
+
+    m.familia("simulador_preempcoes_total", "counter", "Preempções realizadas (monitor de deadlock e API de controle).");
+    for h in hospitais {
+        let preempcoes = h.recursos.preempcoes.load(Ordering::SeqCst);
+        m.amostra("simulador_preempcoes_total", &[("hospital", h.nome)], preempcoes as f64);
+    }
+
+    // Histogramas de tempo (segundos de tempo simulado)
+    m.familia("simulador_tempo_espera_segundos", "histogram", "Tempo simulado de cada espera encerrada na fila de um recurso.");
+    for h in hospitais {
+        let esperas: Vec<f64> =
+            h.recursos.historico_uso.esperas.lock().unwrap().iter().filter(|e| e.fim > 0.0).map(|e| e.fim - e.inicio).collect();
+        m.histograma("simulador_tempo_espera_segundos", &[("hospital", h.nome)], &esperas);
+    }
+
+    m.familia("simulador_tempo_atendimento_segundos", "histogram", "Duração total (tempo simulado) dos atendimentos concluídos.");
+    let duracoes: Vec<f64> = estatisticas.atendimentos.lock().unwrap().values().map(|d| d.as_secs_f64()).collect();
+    m.histograma("simulador_tempo_atendimento_segundos", &[], &duracoes);
+
+    m.texto
+}
+
+// ---------------- Endpoint HTTP ----------------
+
+/// Serve `GET /metrics` na `endereco` para o Prometheus.
+pub async fn iniciar_servidor_metricas(simulacao: Arc<Simulacao>, endereco: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(endereco).await?;
+    println!("📈 Métricas Prometheus em http://{}/metrics", endereco);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(atender_coleta(stream, simulacao.clone()));
+    }
+}
+
+async fn atender_coleta(mut stream: TcpStream, simulacao: Arc<Simulacao>) {
+    let Some(requisicao) = http::ler_requisicao(&mut stream).await else { return };
+
+    let _ = match (requisicao.metodo.as_str(), requisicao.caminho.as_str()) {
+        ("GET", "/metrics") => {
+            let corpo = renderizar_metricas(&simulacao).await;
+            http::responder(&mut stream, "200 OK", "text/plain; version=0.0.4; charset=utf-8", corpo.as_bytes()).await
+        }
+        _ => http::responder(&mut stream, "404 Not Found", "text/plain; charset=utf-8", b"use /metrics\n").await,
+    };
+}
@@ -1,8 +1,9 @@
 // monitor.rs
 use std::sync::Arc;
-use tokio::time::{sleep, Duration, Instant};
+use tokio::time::Duration;
 use tokio::sync::Mutex; 
 use crate::recursos::Recursos;
+use crate::deadlock;
 use crate::monitor_gui::EstadoRecursosGUI;
 use crate::HistoricoRecursos;
 
@@ -23,7 +24,12 @@ pub async fn iniciar_monitor(
     historico: Historico, 
     logs: Logs, // Arc<tokio::sync::Mutex<Vec<String>>>
 ) {
-    let mut ultimo_log = Instant::now();
+    let mut ultimo_log = recursos.controle.agora();
+    // Quantas violações da ordem global já foram levadas ao log
+    let mut violacoes_registradas = 0;
+    // Pacientes em deadlock no ciclo anterior: só há preempção se o mesmo conjunto
+    // aparecer em dois ciclos seguidos (descarta reservas que estavam em andamento)
+    let mut suspeitos_anteriores: Vec<String> = vec![];
 
     loop {
         // Bloco para garantir que os locks sejam liberados após o uso.
@@ -38,37 +44,32 @@ pub async fn iniciar_monitor(
         // Record resource snapshot
         historico_lock.registrar(&recursos);
         
-        // CORREÇÃO APLICADA: Substituído .lock().unwrap() por .lock().await
-        // A função é ASYNC, e os campos deadlock_... são tokio::sync::Mutex
-        let deadlock_m = recursos.deadlock_medicos.lock().await.clone();
-        let deadlock_s = recursos.deadlock_salas.lock().await.clone();
-        let deadlock_l = recursos.deadlock_leitos.lock().await.clone();
-        
-        let deadlock_detectado = !deadlock_m.is_empty()
-            || !deadlock_s.is_empty()
-            || !deadlock_l.is_empty();
+        // Detecção de deadlock: algoritmo de detecção sobre a fotografia de posse e pedidos.
+        // As listas de deadlock dos pools indicam apenas espera longa e não disparam preempção.
+        let retrato = recursos.retrato_alocacao().await;
+        let em_deadlock = deadlock::detectar_deadlock(&retrato);
+
+        if !em_deadlock.is_empty() && em_deadlock == suspeitos_anteriores {
+            // Escolhe a vítima entre quem mantém recursos (quem só espera não libera nada ao ser preemptado):
+            // a que mantém menos recursos (menos trabalho perdido), com desempate pelo nome
+            let vitima = em_deadlock
+                .iter()
+                .filter_map(|nome| retrato.alocacao.get(nome).map(|tipos| (tipos.len(), nome)))
+                .min()
+                .map(|(_, nome)| nome.clone());
+
+            let log_msg_ciclo = format!("🔁 Espera circular detectada entre: {}", em_deadlock.join(", "));
+            println!("\n[J.A.R.V.I.S.] {}", log_msg_ciclo);
+            estado.registrar_log(&log_msg_ciclo);
+            logs_lock.push(log_msg_ciclo);
+            recursos.registrar_ciclo(&em_deadlock).await;
 
-        if deadlock_detectado {
-            // Lógica de Tratamento de Deadlock (Preempção)
-            
-            let mut vitima = None;
-            
-            // Escolhe a vítima: Pela ordem de detecção (a mais simples)
-            if let Some(p) = deadlock_m.iter().next() {
-                vitima = Some(p.clone());
-            } else if let Some(p) = deadlock_s.iter().next() {
-                vitima = Some(p.clone());
-            } else if let Some(p) = deadlock_l.iter().next() {
-                vitima = Some(p.clone());
-            }
-            
-            // Se encontramos uma vítima, tentamos "contornar" o deadlock
             if let Some(nome_vitima) = vitima {
-                // **AÇÃO DE RECUPERAÇÃO**
-                recursos.preempcao_paciente(&nome_vitima);
+                // **AÇÃO DE RECUPERAÇÃO**: o atendimento da vítima é cancelado e as reservas dela voltam
+                recursos.preempcao_paciente(&nome_vitima, "monitor de deadlock").await;
                 
                 let log_msg_contorno = format!(
-                    "♻️ Deadlock Contornado: Paciente {} foi ABORTADO/LIMPO (preempção) para quebrar o ciclo de espera.",
+                    "♻️ Deadlock Contornado: atendimento de {} CANCELADO (preempção); as reservas dele são devolvidas para quebrar o ciclo de espera.",
                     nome_vitima
                 );
                 
@@ -78,37 +79,53 @@ pub async fn iniciar_monitor(
                 estado.registrar_log(&log_msg_contorno); 
                 logs_lock.push(log_msg_contorno);
             }
+            suspeitos_anteriores.clear();
+        } else {
+            suspeitos_anteriores = em_deadlock;
         }
         
-        // Log de Status Periódico
-        let medicos_disp = recursos.medicos.available_permits();
-        let salas_disp = recursos.salas_cirurgia.available_permits();
-        let leitos_disp = recursos.leitos.available_permits();
+        // Violações da ordem global registradas pelos Recursos desde o último ciclo
+        {
+            let violacoes = recursos.violacoes_ordem.lock().await;
+            for violacao in violacoes.iter().skip(violacoes_registradas) {
+                let icone = if violacao.rejeitada { "❌" } else { "⚠️" };
+                let log_msg = format!("{} Ordem global violada: {}", icone, violacao);
+                println!("\n[J.A.R.V.I.S.] {}", log_msg);
+                estado.registrar_log(&log_msg);
+                logs_lock.push(log_msg);
+            }
+            violacoes_registradas = violacoes.len();
+        }
+
+        // Log de Status Periódico e atualização do estado da GUI
+        let disponiveis: Vec<usize> = recursos.pools.iter().map(|p| p.semaforo.available_permits()).collect();
 
-        if ultimo_log.elapsed().as_secs() >= 1 {
-            let log_msg = format!(
-                "🔹 Status: Médicos disp: {}/{}, Salas disp: {}/{}, Leitos disp: {}/{}",
-                medicos_disp, estado.medicos_max,
-                salas_disp, estado.salas_max,
-                leitos_disp, estado.leitos_max,
-            );
+        if recursos.controle.decorrido(ultimo_log).as_secs() >= 1 {
+            let status: Vec<String> = estado
+                .recursos
+                .iter()
+                .zip(&disponiveis)
+                .map(|(pool, disp)| format!("{} disp: {}/{}", pool.config.nome, disp, pool.max))
+                .collect();
+            let log_msg = format!("🔹 Status: {}", status.join(", "));
             
             println!("\n[J.A.R.V.I.S.] {}", log_msg);
             estado.registrar_log(&log_msg);
             logs_lock.push(log_msg);
             
-            ultimo_log = Instant::now();
+            ultimo_log = recursos.controle.agora();
         }
         
-        // Atualiza o estado da GUI
-        estado.medicos = medicos_disp;
-        estado.salas = salas_disp;
-        estado.leitos = leitos_disp;
+        estado.sincronizar(&recursos).await;
         
-        // Os MutexGuards 'estado', 'historico_lock' e 'logs_lock' são liberados aqui 
-        // ao sair do escopo, o que é seguro em tokio.
+        // Os MutexGuards 'estado', 'historico_lock' e 'logs_lock' são liberados antes da espera:
+        // com a simulação pausada a espera não termina, e a GUI e a API continuam lendo o estado.
+        drop(estado);
+        drop(historico_lock);
+        drop(logs_lock);
         
-        // Intervalo de atualização (AWAIT, por isso precisamos do tokio::sync::Mutex para logs, estado e historico)
-        sleep(Duration::from_millis(500)).await;
+        // Intervalo de atualização em tempo simulado: acompanha a velocidade e para junto com a simulação
+        // (AWAIT, por isso precisamos do tokio::sync::Mutex para logs, estado e historico)
+        recursos.controle.dormir(Duration::from_millis(500)).await;
     }
 }
\ No newline at end of file
@@ -4,115 +4,125 @@ use tokio::sync::Mutex;
 use std::sync::Mutex as StdMutex; 
 use std::time::{Instant, Duration};
 use std::collections::HashMap;
-use crate::recursos::Recursos;
-use egui_plot::{Plot, Line, Legend, Corner, PlotPoints};
+use serde::Serialize;
+use crate::recursos::{Recursos, ConfigRecurso, EventoUso, PoolRecurso, TipoRecurso, LEITO};
+use crate::rede::RedeHospitalar;
+use crate::controle::{VELOCIDADE_MAX, VELOCIDADE_MIN};
+use crate::clinica;
+use crate::simulacao::{AcaoOperador, Simulacao};
+use egui_plot::{Plot, Line, LineStyle, Legend, Corner, PlotPoints};
+
+/// Espelho síncrono de um `PoolRecurso` para a GUI.
+#[derive(Clone, Serialize)]
+pub struct EstadoPoolGUI {
+    pub config: ConfigRecurso,
+    pub disponiveis: usize,
+    pub max: usize,
+    pub em_uso_slots: Vec<Option<String>>,
+    pub fila: Vec<String>,
+    pub deadlock: Vec<String>,
+}
+
+impl EstadoPoolGUI {
+    fn new(config: ConfigRecurso) -> Self {
+        Self {
+            config,
+            disponiveis: config.capacidade,
+            max: config.capacidade,
+            em_uso_slots: vec![None; config.capacidade],
+            fila: vec![],
+            deadlock: vec![],
+        }
+    }
+
+    /// Instâncias em uso (Max - Disponível).
+    pub fn em_uso(&self) -> usize {
+        self.max.saturating_sub(self.disponiveis)
+    }
+}
 
 /// Estrutura que espelha o estado dos recursos do sistema para uso síncrono na GUI.
-///
-/// **CORREÇÃO: Adicionados campos para o recurso 'Exames'.**
-#[derive(Clone)]
+/// Há um `EstadoPoolGUI` para cada tipo de recurso configurado.
+/// Também é a resposta da consulta de estado da API de controle (sem os logs, que têm comando próprio).
+#[derive(Clone, Serialize)]
 pub struct EstadoRecursosGUI {
-    pub medicos: usize,
-    pub medicos_max: usize,
-    pub salas: usize,
-    pub salas_max: usize,
-    pub leitos: usize,
-    pub leitos_max: usize,
-    // NOVO: Recursos de Exames
-    pub exames: usize,
-    pub exames_max: usize,
-    
-    pub medicos_em_uso_slots: Vec<Option<String>>,
-    pub salas_em_uso_slots: Vec<Option<String>>,
-    pub leitos_em_uso_slots: Vec<Option<String>>,
-    // NOVO: Slots de Exames
-    pub exames_em_uso_slots: Vec<Option<String>>,
-
-    pub fila_medicos: Vec<String>,
-    pub fila_salas: Vec<String>,
-    pub fila_leitos: Vec<String>,
-    // NOVO: Fila de Exames
-    pub fila_exames: Vec<String>,
-
-    pub deadlock_medicos: Vec<String>,
-    pub deadlock_salas: Vec<String>,
-    pub deadlock_leitos: Vec<String>,
-    // NOVO: Deadlock de Exames
-    pub deadlock_exames: Vec<String>,
+    pub recursos: Vec<EstadoPoolGUI>,
 
     pub atendimentos_total: u32,
     pub pacientes_atendidos: Vec<String>,
+    // Pacientes que deixaram o hospital sem atendimento (balking/reneging)
+    pub desistencias_total: u32,
+    pub pacientes_desistentes: Vec<String>,
     pub tempos_espera: Vec<(String, Duration)>,
     pub progresso_pacientes: HashMap<String, f32>,
+    #[serde(skip)]
     pub logs: Vec<String>,
 }
 
 impl EstadoRecursosGUI {
-    /// Cria uma nova instância do estado da GUI.
-    /// **CORREÇÃO: Adicionado 'exames_max' ao construtor.**
-    pub fn new(medicos_max: usize, salas_max: usize, leitos_max: usize, exames_max: usize) -> Self {
+    /// Cria uma nova instância do estado da GUI a partir da configuração dos recursos.
+    pub fn new(config: &[ConfigRecurso]) -> Self {
         Self {
-            medicos: medicos_max,
-            medicos_max,
-            salas: salas_max,
-            salas_max,
-            leitos: leitos_max,
-            leitos_max,
-            exames: exames_max, // NOVO
-            exames_max,         // NOVO
-            
-            medicos_em_uso_slots: vec![None; medicos_max],
-            salas_em_uso_slots: vec![None; salas_max],
-            leitos_em_uso_slots: vec![None; leitos_max],
-            exames_em_uso_slots: vec![None; exames_max], // NOVO
-
-            fila_medicos: vec![],
-            fila_salas: vec![],
-            fila_leitos: vec![],
-            fila_exames: vec![], // NOVO
-            
-            deadlock_medicos: vec![],
-            deadlock_salas: vec![],
-            deadlock_leitos: vec![],
-            deadlock_exames: vec![], // NOVO
+            recursos: config.iter().copied().map(EstadoPoolGUI::new).collect(),
 
             atendimentos_total: 0,
             pacientes_atendidos: vec![],
+            desistencias_total: 0,
+            pacientes_desistentes: vec![],
             tempos_espera: vec![],
             progresso_pacientes: HashMap::new(),
             logs: vec![],
         }
     }
 
+    pub fn pool_mut(&mut self, tipo: &str) -> Option<&mut EstadoPoolGUI> {
+        self.recursos.iter_mut().find(|r| r.config.tipo == tipo)
+    }
+
+    /// Coloca o paciente na fila exibida de um recurso (críticos vão para a frente).
+    pub fn entrar_fila(&mut self, tipo: TipoRecurso, paciente: &str, prioritario: bool) {
+        if let Some(pool) = self.pool_mut(tipo) {
+            if prioritario {
+                pool.fila.insert(0, paciente.to_string());
+            } else {
+                pool.fila.push(paciente.to_string());
+            }
+        }
+    }
+
+    pub fn sair_fila(&mut self, tipo: TipoRecurso, paciente: &str) {
+        if let Some(pool) = self.pool_mut(tipo) {
+            pool.fila.retain(|n| n != paciente);
+        }
+    }
+
     /// Atualiza o estado da GUI a partir da estrutura de recursos principal.
-    /// **CORREÇÃO: Incluída a lógica de atualização para os recursos de Exames.**
     pub fn atualizar_estado(&mut self, recursos: &Recursos) {
-        // 1. Atualiza as contagens de permissões disponíveis
-        self.medicos = recursos.medicos.available_permits();
-        self.salas = recursos.salas_cirurgia.available_permits();
-        self.leitos = recursos.leitos.available_permits();
-        self.exames = recursos.equipamentos_exames.available_permits(); // NOVO: Assumindo nome 'equipamentos_exames'
-        
-        // 2. Copia o estado dos slots de uso
-        self.medicos_em_uso_slots = recursos.slots_medicos.blocking_lock().clone();
-        self.salas_em_uso_slots = recursos.slots_salas.blocking_lock().clone();
-        self.leitos_em_uso_slots = recursos.slots_leitos.blocking_lock().clone();
-        self.exames_em_uso_slots = recursos.slots_exames.blocking_lock().clone(); // NOVO: Assumindo nome 'slots_exames'
-
-        // 3. Atualiza as filas de espera e deadlock
-        // CORREÇÃO ESSENCIAL: Removemos a chamada `.into_iter().map(|t| t.1).collect()`
-        // e simplesmente clonamos o conteúdo do Mutex, assumindo que ele já é Vec<String>
-        // (que é o tipo de destino dos campos self.*).
-        
-        self.fila_medicos = recursos.fila_medicos.blocking_lock().clone();
-        self.fila_salas = recursos.fila_salas.blocking_lock().clone();
-        self.fila_leitos = recursos.fila_leitos.blocking_lock().clone();
-        self.fila_exames = recursos.fila_exames.blocking_lock().clone();
-
-        self.deadlock_medicos = recursos.deadlock_medicos.blocking_lock().clone();
-        self.deadlock_salas = recursos.deadlock_salas.blocking_lock().clone();
-        self.deadlock_leitos = recursos.deadlock_leitos.blocking_lock().clone();
-        self.deadlock_exames = recursos.deadlock_exames.blocking_lock().clone();
+        for estado in self.recursos.iter_mut() {
+            let Some(pool) = recursos.pool(estado.config.tipo) else { continue };
+
+            // 1. Atualiza as contagens de permissões disponíveis (e a capacidade, que pode mudar em execução)
+            estado.disponiveis = pool.semaforo.available_permits();
+            estado.max = pool.capacidade();
+            // 2. Copia o estado dos slots de uso
+            estado.em_uso_slots = pool.slots.blocking_lock().clone();
+            // 3. Atualiza as filas de espera e deadlock
+            estado.fila = pool.fila.blocking_lock().clone();
+            estado.deadlock = pool.deadlock.blocking_lock().clone();
+        }
+    }
+
+    /// Versão assíncrona de `atualizar_estado`, para uso dentro do runtime (monitor e API de controle).
+    pub async fn sincronizar(&mut self, recursos: &Recursos) {
+        for estado in self.recursos.iter_mut() {
+            let Some(pool) = recursos.pool(estado.config.tipo) else { continue };
+
+            estado.disponiveis = pool.semaforo.available_permits();
+            estado.max = pool.capacidade();
+            estado.em_uso_slots = pool.slots.lock().await.clone();
+            estado.fila = pool.fila.lock().await.clone();
+            estado.deadlock = pool.deadlock.lock().await.clone();
+        }
     }
 
     pub fn atualizar_progresso(&mut self, paciente: &str, progresso: f32) {
@@ -129,6 +139,11 @@ impl EstadoRecursosGUI {
         self.tempos_espera.push((paciente.to_string(), duracao));
     }
 
+    pub fn registrar_desistencia(&mut self, paciente: &str) {
+        self.pacientes_desistentes.push(paciente.to_string());
+        self.desistencias_total += 1;
+    }
+
     pub fn registrar_log(&mut self, mensagem: &str) {
         self.logs.push(mensagem.to_string());
         // Limita o histórico de logs para evitar estouro de memória
@@ -138,13 +153,107 @@ impl EstadoRecursosGUI {
     }
 }
 
+/// Ponto do histórico de uso: (Tempo, instâncias em uso e capacidade de cada recurso, na ordem da configuração).
+type PontoHistorico = (f64, Vec<usize>, Vec<usize>);
+
+/// Cores das linhas do gráfico de uso, atribuídas pela ordem dos recursos.
+pub(crate) const CORES_RECURSOS: [egui::Color32; 8] = [
+    egui::Color32::BLUE,
+    egui::Color32::from_rgb(255, 165, 0), // Laranja
+    egui::Color32::GREEN,
+    egui::Color32::from_rgb(128, 0, 128), // Roxo
+    egui::Color32::from_rgb(0, 191, 255),
+    egui::Color32::from_rgb(220, 20, 60),
+    egui::Color32::from_rgb(139, 69, 19),
+    egui::Color32::GRAY,
+];
+
+/// Campos do formulário de admissão de pacientes durante a execução.
+struct FormularioAdmissao {
+    nome: String,
+    idade: u32,
+    condicao: &'static str,
+    cirurgia: bool,
+    critico: bool,
+    /// Hospital da rede que recebe os pacientes (0 = principal).
+    hospital: usize,
+    /// Quantidade de pacientes do lote aleatório.
+    lote: usize,
+    /// Mensagem da última admissão e se ela deu certo.
+    resultado: Option<(String, bool)>,
+}
+
+impl Default for FormularioAdmissao {
+    fn default() -> Self {
+        Self {
+            nome: String::new(),
+            idade: 40,
+            condicao: "Dor",
+            cirurgia: false,
+            critico: false,
+            hospital: 0,
+            lote: 5,
+            resultado: None,
+        }
+    }
+}
+
+/// Largura inicial das colunas do mapa de calor de ocupação (segundos de simulação).
+const LARGURA_BALDE_PADRAO: f64 = 5.0;
+/// Largura mínima de uma célula do mapa de calor, em pixels.
+const LARGURA_MIN_CELULA: f32 = 4.0;
+
+/// Fração do tempo de cada balde (`[i * largura, (i + 1) * largura)`) em que a instância esteve
+/// ocupada, dados os intervalos de uso dela (`fim` 0.0 = ainda em uso, até `agora`).
+fn ocupacao_por_balde(usos: &[&EventoUso], largura: f64, agora: f64) -> Vec<f64> {
+    let baldes = (agora / largura).ceil().max(1.0) as usize;
+    let mut ocupado = vec![0.0; baldes];
+    for evento in usos {
+        let fim = if evento.fim == 0.0 { agora } else { evento.fim };
+        let primeiro = (evento.inicio / largura).floor() as usize;
+        for (i, balde) in ocupado.iter_mut().enumerate().skip(primeiro) {
+            let (inicio_balde, fim_balde) = (i as f64 * largura, (i + 1) as f64 * largura);
+            if inicio_balde >= fim {
+                break;
+            }
+            *balde += (fim.min(fim_balde) - evento.inicio.max(inicio_balde)).max(0.0);
+        }
+    }
+    // O último balde pode estar incompleto: a fração é sobre o trecho já simulado dele
+    ocupado.iter().enumerate()
+        .map(|(i, tempo)| (tempo / largura.min(agora - i as f64 * largura)).min(1.0))
+        .collect()
+}
+
+/// Cor do mapa de calor: azul escuro (ocioso) → amarelo → vermelho (sempre ocupado).
+fn cor_ocupacao(fracao: f64) -> egui::Color32 {
+    let mistura = |a: u8, b: u8, t: f64| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    let (de, para, t) = if fracao < 0.5 {
+        ((20, 40, 90), (240, 200, 40), fracao * 2.0)
+    } else {
+        ((240, 200, 40), (200, 30, 30), (fracao - 0.5) * 2.0)
+    };
+    egui::Color32::from_rgb(mistura(de.0, para.0, t), mistura(de.1, para.1, t), mistura(de.2, para.2, t))
+}
+
+/// Avanço do botão de passo único quando `com_passo_unico` não é usado.
+const PASSO_UNICO_PADRAO: Duration = Duration::from_secs(1);
+
 pub struct MonitorGUI {
     estado: Arc<Mutex<EstadoRecursosGUI>>,
     recursos: Arc<Recursos>,
     blink_start: Instant,
-    /// Histórico de uso de recursos para plotagem (Tempo, Médicos em Uso, Salas em Uso, Leitos em Uso, Exames em Uso).
-    historico: Arc<StdMutex<Vec<(f64, usize, usize, usize, usize)>>>, // CORREÇÃO: Adicionado 'usize' para Exames
-    inicio: Instant,
+    /// Histórico de uso de recursos para plotagem (Tempo, uso de cada recurso).
+    historico: Arc<StdMutex<Vec<PontoHistorico>>>,
+    /// Tempo simulado percorrido pelo botão de passo único.
+    passo_unico: Duration,
+    /// Simulação em execução, para admitir pacientes pela GUI (opcional).
+    simulacao: Option<Arc<Simulacao>>,
+    admissao: FormularioAdmissao,
+    /// Largura (em segundos de simulação) de cada coluna do mapa de calor.
+    largura_balde: f64,
+    /// Rede regional exibida na visão de rede (opcional).
+    rede: Option<Arc<RedeHospitalar>>,
 }
 
 impl MonitorGUI {
@@ -154,48 +263,196 @@ impl MonitorGUI {
             recursos,
             blink_start: Instant::now(),
             historico: Arc::new(StdMutex::new(vec![])),
-            inicio: Instant::now(),
+            passo_unico: PASSO_UNICO_PADRAO,
+            simulacao: None,
+            admissao: FormularioAdmissao::default(),
+            largura_balde: LARGURA_BALDE_PADRAO,
+            rede: None,
         }
     }
 
+    /// Ativa a visão da rede hospitalar (ocupação de cada hospital e transferências).
+    pub fn com_rede(mut self, rede: Arc<RedeHospitalar>) -> Self {
+        self.rede = Some(rede);
+        self
+    }
+
+    /// Ativa o formulário de admissão de pacientes durante a execução.
+    pub fn com_simulacao(mut self, simulacao: Arc<Simulacao>) -> Self {
+        self.simulacao = Some(simulacao);
+        self
+    }
+
+    /// Define quanto tempo simulado o botão de passo único avança (ex.: uma unidade de tempo).
+    pub fn com_passo_unico(mut self, passo: Duration) -> Self {
+        self.passo_unico = passo;
+        self
+    }
+
     /// Lógica para determinar se o elemento deve piscar.
     fn blink(&self) -> bool {
-        (self.blink_start.elapsed().as_millis() / 500) % 2 == 0
+        (self.blink_start.elapsed().as_millis() / 500).is_multiple_of(2)
+    }
+
+    /// Barra de controle da execução: pausa/retomada, passo único e velocidade.
+    /// Vale para todos os pacientes e para o monitor, pois todos usam o mesmo `ControleSimulacao`.
+    fn mostrar_controles(&self, ui: &mut egui::Ui) {
+        let controle = &self.recursos.controle;
+
+        ui.horizontal(|ui| {
+            if controle.pausado() {
+                if ui.button("▶ Retomar").clicked() {
+                    controle.retomar();
+                }
+                if ui
+                    .button("⏭ Passo")
+                    .on_hover_text(format!("Avança {:.1}s de tempo simulado e pausa", self.passo_unico.as_secs_f64()))
+                    .clicked()
+                {
+                    controle.passo(self.passo_unico);
+                }
+            } else if ui.button("⏸ Pausar").clicked() {
+                controle.pausar();
+            }
+
+            ui.separator();
+            let mut velocidade = controle.velocidade();
+            let slider = egui::Slider::new(&mut velocidade, VELOCIDADE_MIN..=VELOCIDADE_MAX)
+                .logarithmic(true)
+                .text("Velocidade")
+                .suffix("x");
+            if ui.add(slider).changed() {
+                controle.definir_velocidade(velocidade);
+            }
+
+            if controle.pausado() {
+                ui.label(egui::RichText::new("⏸️ PAUSADO").strong().color(egui::Color32::YELLOW));
+            }
+        });
+    }
+
+    /// Formulário para admitir um paciente (ou um lote aleatório) com a simulação rodando.
+    /// Os pacientes seguem o mesmo caminho clínico dos iniciais, numa tarefa própria.
+    fn mostrar_admissao(&mut self, ui: &mut egui::Ui, simulacao: &Simulacao) {
+        let form = &mut self.admissao;
+
+        egui::Grid::new("formulario_admissao").num_columns(2).spacing([10.0, 4.0]).show(ui, |ui| {
+            ui.label("Nome:");
+            ui.text_edit_singleline(&mut form.nome);
+            ui.end_row();
+
+            ui.label("Idade:");
+            ui.add(egui::DragValue::new(&mut form.idade).range(0..=120));
+            ui.end_row();
+
+            ui.label("Condição:");
+            egui::ComboBox::from_id_source("condicao_admissao")
+                .selected_text(form.condicao)
+                .show_ui(ui, |ui| {
+                    for condicao in clinica::condicoes() {
+                        ui.selectable_value(&mut form.condicao, condicao, condicao);
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Atendimento:");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut form.cirurgia, "Precisa de cirurgia");
+                ui.checkbox(&mut form.critico, "Crítico (prioridade)");
+            });
+            ui.end_row();
+
+            if simulacao.rede.hospitais.len() > 1 {
+                ui.label("Hospital:");
+                egui::ComboBox::from_id_source("hospital_admissao")
+                    .selected_text(simulacao.rede.hospitais[form.hospital].nome)
+                    .show_ui(ui, |ui| {
+                        for (i, hospital) in simulacao.rede.hospitais.iter().enumerate() {
+                            ui.selectable_value(&mut form.hospital, i, hospital.nome);
+                        }
+                    });
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("➕ Admitir").clicked() {
+                let nome = form.nome.trim();
+                form.resultado = Some(if nome.is_empty() {
+                    ("Informe o nome do paciente.".to_string(), false)
+                } else {
+                    let paciente = Simulacao::paciente_avulso(nome, form.idade, form.condicao, form.cirurgia, form.critico as u8);
+                    match simulacao.admitir(paciente, form.hospital) {
+                        Ok(()) => {
+                            let mensagem = format!("✅ {} admitido.", nome);
+                            form.nome.clear();
+                            (mensagem, true)
+                        }
+                        Err(e) => (format!("❌ {}", e), false),
+                    }
+                });
+            }
+
+            ui.separator();
+            ui.add(egui::DragValue::new(&mut form.lote).range(1..=50).suffix(" pacientes"));
+            if ui.button("🎲 Admitir lote aleatório").clicked() {
+                let mut rng = rand::rng();
+                let admitidos: Vec<String> = (0..form.lote)
+                    .map(|_| simulacao.paciente_aleatorio(&mut rng))
+                    .filter_map(|paciente| {
+                        let nome = paciente.nome.clone();
+                        simulacao.admitir(paciente, form.hospital).ok().map(|()| nome)
+                    })
+                    .collect();
+                form.resultado = Some((format!("✅ Lote admitido: {}", admitidos.join(", ")), true));
+            }
+        });
+
+        if let Some((mensagem, sucesso)) = &form.resultado {
+            let cor = if *sucesso { egui::Color32::GREEN } else { egui::Color32::RED };
+            ui.label(egui::RichText::new(mensagem).color(cor));
+        }
     }
 
     /// Desenha o painel de recursos, seus slots de uso e as filas de espera/deadlock.
     fn mostrar_recursos(&self, ui: &mut egui::Ui) {
         let estado = self.estado.blocking_lock();
 
-        // CORREÇÃO: Adicionado o recurso Exames para exibição
-        let recursos_info = vec![
-            ("👨‍⚕️ Médicos", &estado.medicos_em_uso_slots, &estado.fila_medicos, &estado.deadlock_medicos),
-            ("🏥 Salas Cirurgia", &estado.salas_em_uso_slots, &estado.fila_salas, &estado.deadlock_salas),
-            ("🛏️ Leitos", &estado.leitos_em_uso_slots, &estado.fila_leitos, &estado.deadlock_leitos),
-            ("🔬 Exames", &estado.exames_em_uso_slots, &estado.fila_exames, &estado.deadlock_exames), // NOVO
-        ];
-
         let max_scroll_height = ui.available_height() * 0.15;
+        // Hospital desta janela na rede da simulação, para os controles de capacidade
+        let hospital = self.simulacao.as_ref().and_then(|simulacao| {
+            let indice = simulacao.rede.hospitais.iter().position(|h| Arc::ptr_eq(&h.recursos, &self.recursos))?;
+            Some((simulacao, indice))
+        });
 
-        // Usa quatro colunas para acomodar o novo recurso
-        ui.columns(4, |columns| {
-            for (col_idx, (nome_recurso, slots, fila, deadlock)) in recursos_info.into_iter().enumerate() {
+        // Uma coluna por tipo de recurso configurado
+        ui.columns(estado.recursos.len().max(1), |columns| {
+            for (col_idx, pool) in estado.recursos.iter().enumerate() {
+                let nome_recurso = pool.config.titulo();
+                let (slots, fila, deadlock) = (&pool.em_uso_slots, &pool.fila, &pool.deadlock);
                 let ui = &mut columns[col_idx];
                 ui.vertical(|ui| {
-                    ui.heading(nome_recurso);
+                    ui.heading(&nome_recurso);
+                    if let (Some((simulacao, indice)), Some(real)) = (hospital, self.recursos.pool(pool.config.tipo)) {
+                        self.mostrar_capacidade(ui, simulacao, indice, real);
+                    }
                     ui.separator();
 
                     // Exibição dos Slots de Uso
                     for (i, uso) in slots.iter().enumerate() {
-                        let (texto, cor) = match uso {
-                            Some(paciente) => (format!("Em uso: {}", paciente), egui::Color32::DARK_RED),
-                            None => ("Disponível".to_string(), egui::Color32::DARK_GREEN),
-                        };
-
                         ui.horizontal(|ui| {
-                            let label_texto = format!("{} {}:", nome_recurso.split(" ").last().unwrap_or("Recurso"), i + 1);
+                            let label_texto = format!("{} {}:", pool.config.instancia, i + 1);
                             ui.label(egui::RichText::new(label_texto).strong());
-                            ui.label(egui::RichText::new(texto).color(cor));
+                            match uso {
+                                Some(paciente) => {
+                                    let texto = egui::RichText::new(format!("Em uso: {}", paciente)).color(egui::Color32::DARK_RED);
+                                    let resposta = ui.add(egui::Label::new(texto).sense(egui::Sense::click()));
+                                    self.menu_paciente(resposta, &estado, paciente);
+                                }
+                                None => {
+                                    ui.label(egui::RichText::new("Disponível").color(egui::Color32::DARK_GREEN));
+                                }
+                            }
                         });
                     }
 
@@ -220,7 +477,8 @@ impl MonitorGUI {
                                             texto = texto.background_color(egui::Color32::from_rgb(255, 100, 100));
                                         }
                                     }
-                                    ui.label(texto);
+                                    let resposta = ui.add(egui::Label::new(texto).sense(egui::Sense::click()));
+                                    self.menu_paciente(resposta, &estado, paciente);
                                 }
                             });
                     } else {
@@ -231,6 +489,122 @@ impl MonitorGUI {
         });
     }
 
+    /// Botões para abrir ou fechar uma instância do recurso durante a execução. Instâncias em uso
+    /// não são revogadas: numa redução elas saem quando o paciente as devolve.
+    fn mostrar_capacidade(&self, ui: &mut egui::Ui, simulacao: &Simulacao, hospital: usize, pool: &PoolRecurso) {
+        let capacidade = pool.capacidade();
+        ui.horizontal(|ui| {
+            ui.label(format!("Capacidade: {}", capacidade));
+            if ui.add_enabled(capacidade > 1, egui::Button::new("➖")).on_hover_text("Fechar uma instância").clicked() {
+                simulacao.alterar_capacidade(hospital, pool.config.tipo, capacidade - 1);
+            }
+            if ui.button("➕").on_hover_text("Abrir uma instância").clicked() {
+                simulacao.alterar_capacidade(hospital, pool.config.tipo, capacidade + 1);
+            }
+        });
+        let pendente = pool.reducao_pendente();
+        if pendente > 0 {
+            ui.label(egui::RichText::new(format!("⏳ {} em uso a retirar", pendente)).color(egui::Color32::YELLOW));
+        }
+    }
+
+    /// Menu de contexto (clique direito) com as ações do operador sobre um paciente. Alta e
+    /// passar à frente só aparecem quando o paciente está num leito ou numa fila deste hospital.
+    fn menu_paciente(&self, resposta: egui::Response, estado: &EstadoRecursosGUI, nome: &str) {
+        let Some(simulacao) = &self.simulacao else { return };
+        let Some(prioridade) = simulacao.prioridade(nome) else { return };
+        let em_leito = estado.recursos.iter()
+            .any(|pool| pool.config.tipo == LEITO && pool.em_uso_slots.iter().any(|slot| slot.as_deref() == Some(nome)));
+        let na_fila = estado.recursos.iter().any(|pool| pool.fila.iter().any(|n| n == nome));
+
+        resposta.on_hover_text("Botão direito: ações do operador").context_menu(|ui| {
+            ui.label(egui::RichText::new(nome).strong());
+            ui.separator();
+
+            let mut acao = None;
+            if ui.button("🛑 Abortar atendimento").clicked() {
+                acao = Some(AcaoOperador::Abortar);
+            }
+            if prioridade == 1 {
+                if ui.button("⬇ Tornar normal").clicked() {
+                    acao = Some(AcaoOperador::AlterarPrioridade(0));
+                }
+            } else if ui.button("🚨 Tornar crítico").clicked() {
+                acao = Some(AcaoOperador::AlterarPrioridade(1));
+            }
+            if em_leito && ui.button("🏠 Dar alta do leito").clicked() {
+                acao = Some(AcaoOperador::DarAlta);
+            }
+            if na_fila && ui.button("⏫ Passar à frente na fila").clicked() {
+                acao = Some(AcaoOperador::PassarAFrente);
+            }
+
+            if let Some(acao) = acao {
+                if let Err(e) = simulacao.acao_operador(nome, acao) {
+                    simulacao.avisar_operador(format!("⚠️ Ação do operador não executada: {}", e));
+                }
+                ui.close_menu();
+            }
+        });
+    }
+
+    /// Desenha a ocupação de cada hospital da rede e as transferências entre eles.
+    fn mostrar_rede(&self, ui: &mut egui::Ui, rede: &RedeHospitalar) {
+        let transferencias = rede.transferencias.blocking_lock();
+        // As colunas seguem os recursos do hospital principal
+        let tipos: Vec<ConfigRecurso> = rede.hospitais[0].recursos.pools.iter().map(|p| p.config).collect();
+
+        egui::Grid::new("grid_rede").striped(true).show(ui, |ui| {
+            ui.label(egui::RichText::new("Hospital").strong());
+            for config in &tipos {
+                ui.label(egui::RichText::new(config.titulo()).strong());
+            }
+            ui.label(egui::RichText::new("🚑 Recebidos / Enviados").strong());
+            ui.end_row();
+
+            for hospital in &rede.hospitais {
+                ui.label(hospital.nome);
+                for config in &tipos {
+                    match hospital.recursos.pool(config.tipo) {
+                        Some(pool) => {
+                            let livres = pool.semaforo.available_permits();
+                            let capacidade = pool.capacidade();
+                            let fila = pool.fila.blocking_lock().len();
+                            let cor = if livres == 0 { egui::Color32::DARK_RED } else { egui::Color32::DARK_GREEN };
+                            let texto = format!("{}/{} em uso (fila {})", capacidade.saturating_sub(livres), capacidade, fila);
+                            ui.label(egui::RichText::new(texto).color(cor));
+                        }
+                        None => {
+                            ui.label("-");
+                        }
+                    }
+                }
+                let recebidos = transferencias.iter().filter(|t| t.destino == hospital.nome).count();
+                let enviados = transferencias.iter().filter(|t| t.origem == hospital.nome).count();
+                ui.label(format!("{} / {}", recebidos, enviados));
+                ui.end_row();
+            }
+        });
+
+        ui.separator();
+        if transferencias.is_empty() {
+            ui.label("Nenhuma transferência até agora.");
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("scroll_transferencias")
+            .max_height(120.0)
+            .show(ui, |ui| {
+                for t in transferencias.iter().rev() {
+                    ui.label(format!(
+                        "🚑 {}: {} → {} (sem {}), saída {:.1}s, chegada {:.1}s",
+                        t.paciente, t.origem, t.destino, t.motivo, t.saida, t.chegada
+                    ));
+                }
+            });
+    }
+
     /// Desenha as barras de progresso dos pacientes em atendimento.
     fn mostrar_progresso(&self, ui: &mut egui::Ui) {
         let estado = self.estado.blocking_lock();
@@ -250,7 +624,8 @@ impl MonitorGUI {
                 pacientes_progresso.sort_by_key(|a| a.0);
                 for (paciente, progresso) in pacientes_progresso {
                     ui.horizontal(|ui| {
-                        ui.label(paciente);
+                        let resposta = ui.add(egui::Label::new(paciente.as_str()).sense(egui::Sense::click()));
+                        self.menu_paciente(resposta, &estado, paciente);
                         let largura = ui.available_width() * 0.8;
                         ui.add_sized([largura, 18.0], egui::ProgressBar::new(*progresso).show_percentage());
                     });
@@ -283,12 +658,96 @@ impl MonitorGUI {
                         texto = texto.color(egui::Color32::YELLOW);
                     } else if log.contains("❌") { // Erro/Deadlock
                         texto = texto.color(egui::Color32::RED).strong();
+                    } else if log.contains("🛂") { // Auditoria de ações do operador
+                        texto = texto.color(egui::Color32::from_rgb(200, 150, 255)).strong();
                     }
                     ui.label(texto);
                 }
             });
     }
 
+    /// Desenha o mapa de calor de ocupação: uma linha por instância de recurso (ex.: "Leito 3"),
+    /// uma coluna por intervalo de tempo, com a cor dada pela fração do intervalo em que a
+    /// instância esteve ocupada. A última coluna traz a média de toda a simulação.
+    fn mostrar_mapa_calor(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Largura de cada coluna:");
+            ui.add(egui::DragValue::new(&mut self.largura_balde).range(0.5..=120.0).speed(0.5).suffix(" s"));
+        });
+
+        let agora = self.recursos.controle.agora().as_secs_f64();
+        if agora <= 0.0 {
+            ui.label("Aguardando dados de uso...");
+            return;
+        }
+
+        // Linhas: (rótulo, ocupação por balde, média)
+        let mut linhas: Vec<(String, Vec<f64>, f64)> = vec![];
+        for pool in &self.recursos.pools {
+            let Some(eventos) = self.recursos.historico_uso.eventos(pool.config.tipo) else { continue };
+            let eventos = eventos.blocking_lock();
+            let instancias = eventos.iter().map(|e| e.instancia_id).max().unwrap_or(0).max(pool.capacidade());
+            for instancia in 1..=instancias {
+                let usos: Vec<&EventoUso> = eventos.iter().filter(|e| e.instancia_id == instancia).collect();
+                let media = ocupacao_por_balde(&usos, agora, agora)[0];
+                let baldes = ocupacao_por_balde(&usos, self.largura_balde, agora);
+                linhas.push((format!("{} {}", pool.config.instancia, instancia), baldes, media));
+            }
+        }
+
+        let colunas = linhas.first().map_or(1, |(_, baldes, _)| baldes.len());
+        let (largura_rotulo, largura_media, altura_linha) = (110.0, 60.0, 16.0);
+        let largura_celula = ((ui.available_width() - largura_rotulo - largura_media) / colunas as f32).max(LARGURA_MIN_CELULA);
+        let tamanho = egui::vec2(
+            largura_rotulo + largura_celula * colunas as f32 + largura_media,
+            altura_linha * linhas.len() as f32,
+        );
+
+        egui::ScrollArea::horizontal().id_source("scroll_mapa_calor").show(ui, |ui| {
+            let (area, resposta) = ui.allocate_exact_size(tamanho, egui::Sense::hover());
+            let pintor = ui.painter_at(area);
+            let fonte = egui::FontId::proportional(11.0);
+            let mut dica = None;
+
+            for (l, (rotulo, baldes, media)) in linhas.iter().enumerate() {
+                let topo = area.top() + l as f32 * altura_linha;
+                pintor.text(
+                    egui::pos2(area.left(), topo + altura_linha / 2.0),
+                    egui::Align2::LEFT_CENTER,
+                    rotulo,
+                    fonte.clone(),
+                    ui.visuals().text_color(),
+                );
+                for (c, fracao) in baldes.iter().enumerate() {
+                    let celula = egui::Rect::from_min_size(
+                        egui::pos2(area.left() + largura_rotulo + c as f32 * largura_celula, topo),
+                        egui::vec2(largura_celula - 1.0, altura_linha - 1.0),
+                    );
+                    pintor.rect_filled(celula, 0.0, cor_ocupacao(*fracao));
+                    if resposta.hover_pos().is_some_and(|p| celula.contains(p)) {
+                        let inicio = c as f64 * self.largura_balde;
+                        dica = Some(format!(
+                            "{}\n{:.1}s – {:.1}s\nOcupação: {:.0}%",
+                            rotulo, inicio, inicio + self.largura_balde, fracao * 100.0
+                        ));
+                    }
+                }
+                pintor.text(
+                    egui::pos2(area.right(), topo + altura_linha / 2.0),
+                    egui::Align2::RIGHT_CENTER,
+                    format!("{:.0}%", media * 100.0),
+                    fonte.clone(),
+                    cor_ocupacao(*media),
+                );
+            }
+
+            if let Some(dica) = dica {
+                resposta.on_hover_text_at_pointer(dica);
+            }
+        });
+        ui.label(egui::RichText::new("Azul: ociosa · Amarelo: metade do tempo · Vermelho: sempre ocupada. À direita, a média da simulação.").weak());
+    }
+
     /// Desenha o gráfico de linha de uso de recursos ao longo do tempo.
     fn mostrar_graficos(&self, ui: &mut egui::Ui) {
         let historico = self.historico.lock().unwrap();
@@ -297,27 +756,44 @@ impl MonitorGUI {
             return;
         }
 
-        // Os dados de histórico são (tempo_em_segundos, uso_medicos, uso_salas, uso_leitos, uso_exames)
-        let dados_medicos: PlotPoints = historico.iter().map(|(t, m, _, _, _)| [*t, *m as f64]).collect();
-        let dados_salas: PlotPoints = historico.iter().map(|(t, _, s, _, _)| [*t, *s as f64]).collect();
-        let dados_leitos: PlotPoints = historico.iter().map(|(t, _, _, l, _)| [*t, *l as f64]).collect();
-        let dados_exames: PlotPoints = historico.iter().map(|(t, _, _, _, e)| [*t, *e as f64]).collect(); // NOVO
+        let estado = self.estado.blocking_lock();
 
-        let line_medicos = Line::new(dados_medicos).color(egui::Color32::BLUE).name("Médicos em Uso");
-        let line_salas = Line::new(dados_salas).color(egui::Color32::from_rgb(255, 165, 0)).name("Salas em Uso"); // Laranja
-        let line_leitos = Line::new(dados_leitos).color(egui::Color32::GREEN).name("Leitos em Uso");
-        let line_exames = Line::new(dados_exames).color(egui::Color32::from_rgb(128, 0, 128)).name("Exames em Uso"); // Roxo
+        // Os dados de histórico são (tempo_em_segundos, [uso de cada recurso], [capacidade de cada recurso]);
+        // uma linha cheia de uso e uma tracejada de capacidade por recurso
+        let linhas: Vec<Line> = estado
+            .recursos
+            .iter()
+            .enumerate()
+            .flat_map(|(i, pool)| {
+                let uso: PlotPoints = historico
+                    .iter()
+                    .map(|(t, usos, _)| [*t, usos.get(i).copied().unwrap_or(0) as f64])
+                    .collect();
+                let capacidade: PlotPoints = historico
+                    .iter()
+                    .map(|(t, _, capacidades)| [*t, capacidades.get(i).copied().unwrap_or(0) as f64])
+                    .collect();
+                let cor = CORES_RECURSOS[i % CORES_RECURSOS.len()];
+                [
+                    Line::new(uso)
+                        .color(cor)
+                        .name(format!("{} em Uso", pool.config.nome))
+                        .stroke(egui::Stroke::new(2.0, cor)),
+                    Line::new(capacidade)
+                        .color(cor)
+                        .name(format!("{} Capacidade", pool.config.nome))
+                        .style(LineStyle::dashed_loose()),
+                ]
+            })
+            .collect();
 
-        let estado = self.estado.blocking_lock();
+        // Inclui a maior capacidade registrada de cada recurso no eixo Y
+        let max_y = historico.iter().flat_map(|(_, _, capacidades)| capacidades.iter().copied()).max().unwrap_or(0);
 
         Plot::new("plot_recursos")
             .legend(Legend::default().position(Corner::RightTop))
             .include_y(0.0)
-            // Inclui o valor máximo de cada recurso no eixo Y
-            .include_y(estado.medicos_max as f64)
-            .include_y(estado.salas_max as f64)
-            .include_y(estado.leitos_max as f64)
-            .include_y(estado.exames_max as f64) // NOVO
+            .include_y(max_y as f64)
             .allow_drag(true)
             .allow_zoom(true)
             .label_formatter(|name, value| {
@@ -329,22 +805,18 @@ impl MonitorGUI {
                 }
             })
             .show(ui, |plot_ui| {
-                // CORREÇÃO: trocado .with_stroke() por .stroke()
-                plot_ui.line(line_medicos.stroke(egui::Stroke::new(2.0, egui::Color32::BLUE)));
-                plot_ui.line(line_salas.stroke(egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 165, 0))));
-                plot_ui.line(line_leitos.stroke(egui::Stroke::new(2.0, egui::Color32::GREEN)));
-                plot_ui.line(line_exames.stroke(egui::Stroke::new(2.0, egui::Color32::from_rgb(128, 0, 128)))); // NOVO
+                for linha in linhas {
+                    plot_ui.line(linha);
+                }
             });
     }
 }
 
-impl eframe::App for MonitorGUI {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let elapsed = self.inicio.elapsed();
-        let elapsed_secs = elapsed.as_secs();
-        let elapsed_mins = elapsed_secs / 60;
-        let elapsed_secs_remainder = elapsed_secs % 60;
-        let elapsed_time_str = format!("Tempo de Simulação: {:02}m {:02}s", elapsed_mins, elapsed_secs_remainder);
+impl MonitorGUI {
+    /// Atualiza o estado e o histórico do gráfico a partir da simulação. Roda a cada quadro,
+    /// mesmo com outra aba visível, para o histórico não ficar com lacunas.
+    pub fn atualizar(&mut self) {
+        let agora = self.recursos.controle.agora().as_secs_f64();
 
         {
             let mut estado = self.estado.blocking_lock();
@@ -352,25 +824,32 @@ impl eframe::App for MonitorGUI {
             estado.atualizar_estado(&self.recursos); 
 
             // Atualização do histórico (Uso = Max - Disponível)
-            let m_uso = estado.medicos_max - estado.medicos;
-            let s_uso = estado.salas_max - estado.salas;
-            let l_uso = estado.leitos_max - estado.leitos;
-            let e_uso = estado.exames_max - estado.exames; // NOVO
+            let usos: Vec<usize> = estado.recursos.iter().map(|p| p.em_uso()).collect();
+            let capacidades: Vec<usize> = estado.recursos.iter().map(|p| p.max).collect();
 
             // Adiciona ponto ao histórico a cada segundo
             let historico_len = self.historico.lock().unwrap().len();
-            if historico_len == 0 || (self.inicio.elapsed().as_secs_f64() - self.historico.lock().unwrap().last().unwrap().0) >= 1.0 {
-                self.historico.lock().unwrap().push((self.inicio.elapsed().as_secs_f64(), m_uso, s_uso, l_uso, e_uso)); // CORREÇÃO: Adicionado e_uso
+            if historico_len == 0 || (agora - self.historico.lock().unwrap().last().unwrap().0) >= 1.0 {
+                self.historico.lock().unwrap().push((agora, usos, capacidades));
             }
 
             // Remove pontos antigos se o histórico for muito longo (manter no máximo 300 segundos)
-            self.historico.lock().unwrap().retain(|(t, _, _, _, _)| self.inicio.elapsed().as_secs_f64() - *t < 300.0);
+            self.historico.lock().unwrap().retain(|(t, _, _)| agora - *t < 300.0);
         }
 
         // Lógica de piscar (reinicia a cada 500ms)
         if self.blink_start.elapsed() > Duration::from_millis(500) {
             self.blink_start = Instant::now();
         }
+    }
+
+    /// Desenha o monitor: barra de controles no topo e os painéis no centro da janela.
+    pub fn desenhar(&mut self, ctx: &egui::Context) {
+        // Tempo simulado (acompanha a velocidade e não corre com a simulação pausada)
+        let elapsed_secs = self.recursos.controle.agora().as_secs();
+        let elapsed_mins = elapsed_secs / 60;
+        let elapsed_secs_remainder = elapsed_secs % 60;
+        let elapsed_time_str = format!("Tempo de Simulação: {:02}m {:02}s", elapsed_mins, elapsed_secs_remainder);
 
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
@@ -378,6 +857,7 @@ impl eframe::App for MonitorGUI {
                 ui.add_space(20.0);
                 ui.label(egui::RichText::new(elapsed_time_str).strong().color(egui::Color32::WHITE));
             });
+            self.mostrar_controles(ui);
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -392,6 +872,24 @@ impl eframe::App for MonitorGUI {
 
                 ui.add_space(10.0);
 
+                // Painel da Rede Hospitalar (só faz sentido com mais de um hospital)
+                if let Some(rede) = self.rede.as_ref().filter(|rede| rede.hospitais.len() > 1) {
+                    egui::CollapsingHeader::new("🌐 Rede Hospitalar").default_open(true).show(ui, |ui| {
+                        self.mostrar_rede(ui, rede);
+                    });
+
+                    ui.add_space(10.0);
+                }
+
+                // Admissão de pacientes durante a execução
+                if let Some(simulacao) = self.simulacao.clone() {
+                    egui::CollapsingHeader::new("➕ Admitir Pacientes").default_open(false).show(ui, |ui| {
+                        self.mostrar_admissao(ui, &simulacao);
+                    });
+
+                    ui.add_space(10.0);
+                }
+
                 // Painel de Progresso
                 egui::CollapsingHeader::new("Progresso Pacientes em Atendimento").default_open(true).show(ui, |ui| {
                     self.mostrar_progresso(ui);
@@ -415,6 +913,13 @@ impl eframe::App for MonitorGUI {
                     self.mostrar_graficos(ui);
                 });
 
+                ui.add_space(10.0);
+
+                // Mapa de calor de ocupação por instância
+                egui::CollapsingHeader::new("🔥 Mapa de Calor de Ocupação").show(ui, |ui| {
+                    self.mostrar_mapa_calor(ui);
+                });
+
                 ui.add_space(10.0);
                 
                 // Exibe as estatísticas gerais no final
@@ -430,12 +935,10 @@ impl eframe::App for MonitorGUI {
                         0.0
                     };
                     ui.label(format!("Tempo Médio de Espera (Concluídos): {:.2}s", media_espera));
+                    ui.label(format!("Desistências (saíram sem atendimento): {}", estado.desistencias_total));
                 });
                 
             });
         });
-
-        // Solicita repintura contínua para atualizar o estado e o efeito de piscar
-        ctx.request_repaint_after(Duration::from_millis(50));
     }
 }
@@ -1,8 +1,14 @@
-use crate::recursos::{Recursos, usar_recurso, pausa};
+use crate::recursos::{Recursos, FalhaReserva, Paciencia, ReservaRecurso, ReservasConjuntas, TipoRecurso, MEDICO, SALA, LEITO, EXAME, ENFERMEIRO, usar_recurso, pausa};
 use crate::monitor_gui::EstadoRecursosGUI;
+use crate::rede::RedeHospitalar;
+use crate::controle::ControleSimulacao;
+use crate::cancelamento::{Cancelamento, MotivoCancelamento};
+use std::fmt;
+use std::future::Future;
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
 
 // Nota: OwnedSemaphorePermit não é mais importado diretamente, 
 // pois as permissões serão declaradas no escopo do match/if let.
@@ -13,7 +19,153 @@ pub struct Paciente {
     pub idade: u32,
     pub condicao: String,
     pub precisa_cirurgia: bool,
-    pub prioridade: u8, // 0 = normal, 1 = crítico
+    pub prioridade: u8, // 0 = normal, 1 = crítico (na chegada; a atual fica em `sinais`)
+    pub paciencia: Paciencia, // Limites de espera na fila de médicos (ignorados para críticos)
+    pub aquisicao_cirurgia: ModoAquisicao, // Como a equipe cirúrgica é obtida
+    pub trajeto: Option<Trajeto>, // Caminho genérico que substitui o atendimento padrão (cenários)
+    pub prazo: Option<Duration>, // Tempo máximo de atendimento; passado o prazo, o atendimento é cancelado
+    pub sinais: Arc<SinaisPaciente>, // Ações do operador durante o atendimento
+}
+
+/// Sinais que o operador envia a um paciente em atendimento: prioridade atual,
+/// alta antecipada do leito e cancelamento do atendimento.
+pub struct SinaisPaciente {
+    prioridade: AtomicU8,
+    alta: watch::Sender<bool>,
+    /// Cancela o atendimento em qualquer ponto de espera (operador, monitor ou prazo).
+    pub cancelamento: Cancelamento,
+}
+
+impl SinaisPaciente {
+    fn novos(prioridade: u8) -> Self {
+        Self {
+            prioridade: AtomicU8::new(prioridade),
+            alta: watch::Sender::new(false),
+            cancelamento: Cancelamento::novo(),
+        }
+    }
+
+    pub fn prioridade(&self) -> u8 {
+        self.prioridade.load(Ordering::SeqCst)
+    }
+
+    pub fn critico(&self) -> bool {
+        self.prioridade() == 1
+    }
+
+    pub fn definir_prioridade(&self, prioridade: u8) {
+        self.prioridade.store(prioridade, Ordering::SeqCst);
+    }
+
+    /// Encerra antes do tempo a permanência no leito atual.
+    pub fn pedir_alta(&self) {
+        self.alta.send_replace(true);
+    }
+
+    /// Espera um pedido de alta e o consome (só vale para uma internação).
+    async fn aguardar_alta(&self) {
+        let _ = self.alta.subscribe().wait_for(|alta| *alta).await;
+        self.alta.send_replace(false);
+    }
+}
+
+/// Recursos que a etapa de cirurgia precisa ao mesmo tempo.
+pub const EQUIPE_CIRURGIA: &[TipoRecurso] = &[MEDICO, SALA, ENFERMEIRO];
+
+/// Forma como um caminho clínico obtém vários recursos para a mesma etapa.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ModoAquisicao {
+    /// Um recurso por vez, retendo os já obtidos (hold-and-wait).
+    #[default]
+    Incremental,
+    /// Todos os recursos da etapa de uma só vez, ou nenhum.
+    Atomica,
+}
+
+impl fmt::Display for ModoAquisicao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModoAquisicao::Incremental => write!(f, "aquisição incremental"),
+            ModoAquisicao::Atomica => write!(f, "aquisição atômica"),
+        }
+    }
+}
+
+// ---------------- Trajetos Genéricos (Cenários) ----------------
+
+/// Etapa de um trajeto: recursos obtidos no início, tempo de uso e recursos devolvidos
+/// ao final. O que não é devolvido continua retido durante a etapa seguinte.
+#[derive(Debug, Clone)]
+pub struct Etapa {
+    pub nome: &'static str,
+    pub adquirir: Vec<TipoRecurso>,
+    pub liberar: Vec<TipoRecurso>,
+    /// Duração em unidades de tempo (multiplicada pela escala).
+    pub duracao: f64,
+}
+
+impl Etapa {
+    pub fn nova(nome: &'static str, adquirir: &[TipoRecurso], duracao: f64) -> Self {
+        Etapa {
+            nome,
+            adquirir: adquirir.to_vec(),
+            liberar: vec![],
+            duracao,
+        }
+    }
+
+    /// Recursos devolvidos ao final da etapa.
+    pub fn liberando(mut self, tipos: &[TipoRecurso]) -> Self {
+        self.liberar = tipos.to_vec();
+        self
+    }
+}
+
+/// Sequência fixa de etapas percorrida por um paciente de cenário, com horário de chegada.
+/// Tudo o que ainda estiver retido é devolvido ao final do trajeto.
+#[derive(Debug, Clone, Default)]
+pub struct Trajeto {
+    /// Atraso de chegada, em unidades de tempo.
+    pub chegada: f64,
+    pub etapas: Vec<Etapa>,
+    /// `Atomica` devolve o que mantém e pede tudo de novo a cada etapa (sem hold-and-wait).
+    pub modo: ModoAquisicao,
+}
+
+impl Trajeto {
+    pub fn novo(chegada: f64, etapas: Vec<Etapa>) -> Self {
+        Trajeto { chegada, etapas, modo: ModoAquisicao::default() }
+    }
+
+    pub fn com_modo(mut self, modo: ModoAquisicao) -> Self {
+        self.modo = modo;
+        self
+    }
+
+    /// Necessidade máxima declarada ao banqueiro: cada tipo usado no trajeto, uma vez.
+    pub fn necessidades(&self) -> Vec<TipoRecurso> {
+        let mut tipos: Vec<TipoRecurso> = vec![];
+        for tipo in self.etapas.iter().flat_map(|e| e.adquirir.iter()) {
+            if !tipos.contains(tipo) {
+                tipos.push(tipo);
+            }
+        }
+        tipos
+    }
+}
+
+/// Resultado final do atendimento de um paciente.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultadoAtendimento {
+    /// Percorreu todas as etapas do atendimento. Guarda o tempo porta-tratamento
+    /// (da chegada até o início do tratamento definitivo: cirurgia ou leito).
+    Concluido { porta_tratamento: Duration },
+    /// Falhou em alguma reserva (ex.: exclusividade) e foi interrompido.
+    Abortado,
+    /// Teve o atendimento cancelado (operador, preempção ou prazo) no ponto em que estava.
+    Cancelado(MotivoCancelamento),
+    /// Deixou o hospital sem ser atendido (balking ou reneging).
+    Desistiu(FalhaReserva),
 }
 
 impl Paciente {
@@ -25,6 +177,11 @@ impl Paciente {
             condicao: condicao.to_string(),
             precisa_cirurgia,
             prioridade,
+            paciencia: Paciencia::default(),
+            aquisicao_cirurgia: ModoAquisicao::default(),
+            trajeto: None,
+            prazo: None,
+            sinais: Arc::new(SinaisPaciente::novos(prioridade)),
         }
     }
 
@@ -32,12 +189,157 @@ impl Paciente {
         Self::novo(nome, idade, condicao, precisa_cirurgia, prioridade)
     }
 
-    pub async fn atender_com_escala(
+    /// Define a paciência do paciente: tempo máximo de espera pelo médico e
+    /// tamanho máximo de fila que ele aceita enfrentar.
+    pub fn com_paciencia(mut self, espera_max: Duration, fila_max: usize) -> Self {
+        self.paciencia = Paciencia {
+            espera_max: Some(espera_max),
+            fila_max: Some(fila_max),
+        };
+        self
+    }
+
+    /// Define como a equipe cirúrgica é obtida (ver `ModoAquisicao`).
+    pub fn com_aquisicao_cirurgia(mut self, modo: ModoAquisicao) -> Self {
+        self.aquisicao_cirurgia = modo;
+        self
+    }
+
+    /// Substitui o atendimento padrão por um trajeto fixo (ver `cenarios`).
+    pub fn com_trajeto(mut self, trajeto: Trajeto) -> Self {
+        self.trajeto = Some(trajeto);
+        self
+    }
+
+    /// Limita o tempo total de atendimento (em tempo simulado); passado o prazo, o atendimento
+    /// é cancelado com `MotivoCancelamento::PrazoEsgotado`.
+    pub fn com_prazo(mut self, prazo: Duration) -> Self {
+        self.prazo = Some(prazo);
+        self
+    }
+
+    /// Atende o paciente chegando ao hospital `hospital` da rede. Antes da cirurgia e do leito
+    /// as regras de transferência da rede são aplicadas, e o restante do atendimento segue
+    /// no hospital de destino.
+    pub async fn atender_na_rede(&self, rede: Arc<RedeHospitalar>, hospital: usize, escala_tempo: f64) -> ResultadoAtendimento {
+        let chegada = &rede.hospitais[hospital];
+        let hospitais: Vec<_> = rede.hospitais.iter().map(|h| (h.recursos.clone(), h.estado.clone())).collect();
+        let atendimento = self.atender(chegada.recursos.clone(), chegada.estado.clone(), escala_tempo, Some((&rede, hospital)));
+        let resultado = self.cancelavel(&hospitais, atendimento).await;
+        for h in &rede.hospitais {
+            h.recursos.definir_preferencia(&self.nome, false).await;
+        }
+        resultado
+    }
+
+    /// Roda o atendimento até o fim ou até o cancelamento do paciente (operador, preempção ou
+    /// prazo), valendo em todos os `hospitais` por onde ele pode passar. Cancelado, o atendimento
+    /// é descartado no ponto de espera em que estava: as reservas devolvem slots, usos e
+    /// permissões; aqui o paciente sai das filas e listas de deadlock e o cancelamento é registrado.
+    async fn cancelavel(
         &self,
-        recursos: Arc<Recursos>,
-        estado_gui: Arc<Mutex<EstadoRecursosGUI>>,
+        hospitais: &[(Arc<Recursos>, Arc<Mutex<EstadoRecursosGUI>>)],
+        atendimento: impl Future<Output = ResultadoAtendimento>,
+    ) -> ResultadoAtendimento {
+        let cancelamento = &self.sinais.cancelamento;
+        for (recursos, _) in hospitais {
+            recursos.registrar_cancelamento(&self.nome, cancelamento.clone()).await;
+        }
+
+        let prazo = async {
+            match (self.prazo, hospitais.first()) {
+                (Some(prazo), Some((recursos, _))) => {
+                    recursos.controle.dormir(prazo).await;
+                    cancelamento.cancelar(MotivoCancelamento::PrazoEsgotado(prazo));
+                }
+                // Sem prazo, só o operador ou o monitor cancelam
+                _ => std::future::pending().await,
+            }
+        };
+        let resultado = tokio::select! {
+            resultado = cancelamento.executar(atendimento) => resultado,
+            () = prazo => Err(cancelamento.cancelado().await),
+        };
+
+        for (recursos, _) in hospitais {
+            recursos.remover_cancelamento(&self.nome).await;
+        }
+        let motivo = match resultado {
+            Ok(resultado) => return resultado,
+            Err(motivo) => motivo,
+        };
+
+        let log_msg = format!("🛑 {} teve o atendimento cancelado: {}", self.nome, motivo);
+        for (recursos, estado_gui) in hospitais {
+            recursos.liberar_todos(&self.nome).await;
+            let mut estado = estado_gui.lock().await;
+            estado.sincronizar(recursos).await;
+            if estado.progresso_pacientes.contains_key(&self.nome) {
+                estado.atualizar_progresso(&self.nome, 1.0);
+                estado.registrar_log(&log_msg);
+            }
+        }
+        println!("{}", log_msg);
+        ResultadoAtendimento::Cancelado(motivo)
+    }
+
+    /// Aplica as regras de transferência antes de uma etapa que usa `tipo`. Se o paciente for
+    /// transferido, `recursos` e `estado_gui` passam a ser os do destino e o retorno é `true`
+    /// (reservas retidas continuam presas ao hospital de origem e são devolvidas lá).
+    async fn transferir_se_preciso(
+        &self,
+        rede: &mut Option<(&RedeHospitalar, usize)>,
+        tipo: TipoRecurso,
+        recursos: &mut Arc<Recursos>,
+        estado_gui: &mut Arc<Mutex<EstadoRecursosGUI>>,
         escala_tempo: f64,
     ) -> bool {
+        let Some((rede, hospital)) = rede.as_mut() else { return false };
+        let Some(destino) = rede.transferir(&self.nome, *hospital, tipo, escala_tempo).await else {
+            return false;
+        };
+
+        *hospital = destino;
+        *estado_gui = rede.hospitais[destino].estado.clone();
+        let destino = &rede.hospitais[destino].recursos;
+        destino.definir_preferencia(&self.nome, self.sinais.critico()).await;
+        *recursos = destino.clone();
+        true
+    }
+
+    /// Usa o leito por `segundos` ou até o operador dar alta antes disso (retorna `true` nesse caso).
+    async fn ocupar_leito(&self, descricao: &str, segundos: u64, controle: &ControleSimulacao) -> bool {
+        tokio::select! {
+            _ = usar_recurso(descricao, segundos, controle) => false,
+            _ = self.sinais.aguardar_alta() => true,
+        }
+    }
+
+    /// Reserva recusada (ex.: violação da ordem global ou preempção): devolve o que o paciente
+    /// mantém, tira-o das filas em que entrou e encerra o progresso, como na desistência.
+    async fn abortar(&self, recursos: &Recursos, estado_gui: &Mutex<EstadoRecursosGUI>, filas: &[TipoRecurso], falha: String) -> ResultadoAtendimento {
+        recursos.liberar_todos(&self.nome).await;
+        let mut estado = estado_gui.lock().await;
+        for &tipo in filas {
+            estado.sair_fila(tipo, &self.nome);
+        }
+        estado.atualizar_progresso(&self.nome, 1.0);
+        estado.registrar_log(&format!("❌ {} {}", self.nome, falha));
+        ResultadoAtendimento::Abortado
+    }
+
+    async fn atender(
+        &self,
+        mut recursos: Arc<Recursos>,
+        mut estado_gui: Arc<Mutex<EstadoRecursosGUI>>,
+        escala_tempo: f64,
+        mut rede: Option<(&RedeHospitalar, usize)>,
+    ) -> ResultadoAtendimento {
+        recursos.definir_preferencia(&self.nome, self.sinais.critico()).await;
+        if let Some(trajeto) = &self.trajeto {
+            return self.percorrer_trajeto(trajeto, recursos, estado_gui, escala_tempo).await;
+        }
+
         // Nova contagem de etapas:
         // Consulta (1) + Exames (1) + Cirurgia/Leito (1 ou 2)
         // Se cirurgia: 4 etapas (Consulta, Exame, Cirurgia, Leito)
@@ -46,53 +348,57 @@ impl Paciente {
         let mut progresso = 0.0;
         let nome_paciente = self.nome.clone();
 
-        pausa((0.5 * escala_tempo) as u64).await;
-        let inicio_atendimento = Instant::now();
+        pausa((0.5 * escala_tempo) as u64, &recursos.controle).await;
+        let inicio_atendimento = recursos.controle.agora();
 
-        // Variável para manter a permissão do médico, se necessário (Cirurgia)
-        let mut medico_permit_op = None;
+        // Variável para manter a reserva do médico, se necessário (Cirurgia)
+        let mut medico_retido = None;
+        // Tempo até o início do tratamento definitivo (usado no modelo de desfecho clínico)
+        let porta_tratamento;
         
         // -------------------- ETAPA 1: Médico (Consulta/Avaliação) --------------------
         {
             let mut estado = estado_gui.lock().await;
-            if self.prioridade == 1 {
-                estado.fila_medicos.insert(0, nome_paciente.clone());
-            } else {
-                estado.fila_medicos.push(nome_paciente.clone());
-            }
+            estado.entrar_fila(MEDICO, &nome_paciente, self.sinais.critico());
             estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
             estado.registrar_log(&format!("🔹 {} entrou na fila de médicos", nome_paciente));
         }
 
-        let medico_permit = match recursos.reservar_medico(nome_paciente.clone()).await {
-            Ok(permit) => permit,
-            Err(e) => {
+        // Pacientes críticos nunca desistem da fila.
+        let paciencia = if self.sinais.critico() { Paciencia::default() } else { self.paciencia };
+
+        let reserva_medico = match recursos.reservar_com_paciencia(MEDICO, nome_paciente.clone(), "Consulta", paciencia).await {
+            Ok(reserva) => reserva,
+            Err(e) if e.eh_desistencia() => {
                 let mut estado = estado_gui.lock().await;
-                estado.registrar_log(&format!("❌ {} Falha na reserva de médico (Exclusividade): {}", nome_paciente, e));
-                return false;
+                estado.sair_fila(MEDICO, &nome_paciente);
+                estado.atualizar_progresso(&nome_paciente, 1.0);
+                estado.registrar_desistencia(&nome_paciente);
+                estado.registrar_log(&format!("⚠️ {} deixou o hospital sem ser atendido: {}", nome_paciente, e));
+                return ResultadoAtendimento::Desistiu(e);
             }
+            Err(e) => return self.abortar(&recursos, &estado_gui, &[MEDICO], format!("Falha na reserva de médico: {}", e)).await,
         };
 
         {
             let mut estado = estado_gui.lock().await;
-            estado.fila_medicos.retain(|n| n != &nome_paciente);
-            recursos.historico_uso.registrar_inicio(&nome_paciente, &recursos.slots_medicos, &recursos.historico_uso.medico).await;
+            estado.sair_fila(MEDICO, &nome_paciente);
             progresso += 1.0;
             estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
             estado.registrar_log(&format!("✅ {} começou consulta com médico", nome_paciente));
         }
 
-        usar_recurso(&format!("Consulta - {}", nome_paciente), (3.0 * escala_tempo) as u64).await;
+        usar_recurso(&format!("Consulta - {}", nome_paciente), (3.0 * escala_tempo) as u64, &recursos.controle).await;
 
-        // Se precisa de cirurgia, guarda a permissão do médico para mantê-lo na próxima etapa
-        if self.precisa_cirurgia {
-            medico_permit_op = Some(medico_permit);
+        // Se precisa de cirurgia (modo incremental), guarda a reserva do médico para mantê-lo na próxima etapa.
+        // No modo atômico o médico é liberado e volta a ser pedido junto com a equipe cirúrgica.
+        if self.precisa_cirurgia && self.aquisicao_cirurgia == ModoAquisicao::Incremental {
+            medico_retido = Some(reserva_medico);
             let mut estado = estado_gui.lock().await;
             estado.registrar_log(&format!("🩺 {} Manteve médico para cirurgia", nome_paciente));
         } else {
             // Se não precisa de cirurgia, libera o médico após a consulta
-            recursos.liberar_medico_slot(&nome_paciente).await;
-            drop(medico_permit);
+            reserva_medico.devolver().await;
             let mut estado = estado_gui.lock().await;
             estado.registrar_log(&format!("✅ {} liberou médico após consulta", nome_paciente));
         }
@@ -100,37 +406,27 @@ impl Paciente {
         // -------------------- ETAPA 2: Exames (Obrigatório) --------------------
         {
             let mut estado = estado_gui.lock().await;
-            if self.prioridade == 1 {
-                estado.fila_exames.insert(0, nome_paciente.clone());
-            } else {
-                estado.fila_exames.push(nome_paciente.clone());
-            }
+            estado.entrar_fila(EXAME, &nome_paciente, self.sinais.critico());
             estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
             estado.registrar_log(&format!("🔹 {} entrou na fila de exames", nome_paciente));
         }
 
-        let exame_permit = match recursos.reservar_exame(nome_paciente.clone()).await {
-            Ok(permit) => permit,
-            Err(e) => {
-                let mut estado = estado_gui.lock().await;
-                estado.registrar_log(&format!("❌ {} Falha na reserva de exame (Exclusividade): {}", nome_paciente, e));
-                return false;
-            }
+        let reserva_exame = match recursos.reservar(EXAME, nome_paciente.clone(), "Exames").await {
+            Ok(reserva) => reserva,
+            Err(e) => return self.abortar(&recursos, &estado_gui, &[EXAME], format!("Falha na reserva de exame: {}", e)).await,
         };
 
         {
             let mut estado = estado_gui.lock().await;
-            estado.fila_exames.retain(|n| n != &nome_paciente);
-            recursos.historico_uso.registrar_inicio(&nome_paciente, &recursos.slots_exames, &recursos.historico_uso.exame).await;
+            estado.sair_fila(EXAME, &nome_paciente);
             progresso += 1.0;
             estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
             estado.registrar_log(&format!("🔬 {} começou a fazer exames", nome_paciente));
         }
 
-        usar_recurso(&format!("Exame - {}", nome_paciente), (2.0 * escala_tempo) as u64).await;
+        usar_recurso(&format!("Exame - {}", nome_paciente), (2.0 * escala_tempo) as u64, &recursos.controle).await;
 
-        recursos.liberar_exame_slot(&nome_paciente).await;
-        drop(exame_permit);
+        reserva_exame.devolver().await;
         {
             let mut estado = estado_gui.lock().await;
             estado.registrar_log(&format!("✅ {} terminou exames e liberou equipamento", nome_paciente));
@@ -139,141 +435,156 @@ impl Paciente {
         // -------------------- ETAPA 3 e 4: Tratamento (Cirurgia OU Observação/Leito) --------------------
 
         if self.precisa_cirurgia {
-            // --- ETAPA 3: Sala de Cirurgia (com Médico) ---
-            let inicio_sala = Instant::now();
+            // --- ETAPA 3: Cirurgia (Médico + Sala + Enfermeiro) ---
+            if self.transferir_se_preciso(&mut rede, SALA, &mut recursos, &mut estado_gui, escala_tempo).await {
+                // O médico retido na consulta fica no hospital de origem
+                if let Some(medico) = medico_retido.take() {
+                    medico.devolver().await;
+                }
+            }
+            let inicio_sala = recursos.controle.agora();
             {
                 let mut estado = estado_gui.lock().await;
-                if self.prioridade == 1 {
-                    estado.fila_salas.insert(0, nome_paciente.clone());
-                } else {
-                    estado.fila_salas.push(nome_paciente.clone());
+                for tipo in EQUIPE_CIRURGIA {
+                    if *tipo != MEDICO || medico_retido.is_none() {
+                        estado.entrar_fila(tipo, &nome_paciente, self.sinais.critico());
+                    }
                 }
                 estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
-                estado.registrar_log(&format!("🔹 {} entrou na fila de salas", nome_paciente));
+                estado.registrar_log(&format!("🔹 {} entrou na fila de cirurgia ({})", nome_paciente, self.aquisicao_cirurgia));
             }
 
-            let sala_permit = match recursos.reservar_sala(nome_paciente.clone()).await {
-                Ok(permit) => permit,
-                Err(e) => {
-                    let mut estado = estado_gui.lock().await;
-                    estado.registrar_log(&format!("❌ {} Falha na reserva de sala (Exclusividade): {}", nome_paciente, e));
-                    return false;
+            // Reservas da equipe cirúrgica, devolvidas juntas ao final da cirurgia
+            let equipe: ReservasConjuntas = match self.aquisicao_cirurgia {
+                ModoAquisicao::Incremental => {
+                    // Hold-and-wait: o médico da consulta continua retido enquanto espera sala e enfermeiro
+                    // (após uma transferência, o médico também é pedido no hospital de destino)
+                    let mut equipe = vec![];
+                    let faltam: &[TipoRecurso] = match medico_retido.take() {
+                        Some(medico) => {
+                            equipe.push(medico);
+                            &[SALA, ENFERMEIRO]
+                        }
+                        None => EQUIPE_CIRURGIA,
+                    };
+                    for &tipo in faltam {
+                        match recursos.reservar(tipo, nome_paciente.clone(), "Cirurgia").await {
+                            Ok(reserva) => equipe.push(reserva),
+                            Err(e) => {
+                                let falha = format!("Falha na reserva de {}: {}", tipo, e);
+                                return self.abortar(&recursos, &estado_gui, EQUIPE_CIRURGIA, falha).await;
+                            }
+                        }
+                    }
+                    equipe
                 }
+                ModoAquisicao::Atomica => match recursos.reservar_conjunto(EQUIPE_CIRURGIA, nome_paciente.clone(), "Cirurgia").await {
+                    Ok(equipe) => equipe,
+                    Err(e) => {
+                        let falha = format!("Falha na reserva da equipe cirúrgica: {}", e);
+                        return self.abortar(&recursos, &estado_gui, EQUIPE_CIRURGIA, falha).await;
+                    }
+                },
             };
 
+            porta_tratamento = recursos.controle.decorrido(inicio_atendimento);
             {
-                let espera_sala = inicio_sala.elapsed();
+                let espera_sala = recursos.controle.decorrido(inicio_sala);
                 let mut estado = estado_gui.lock().await;
-                estado.fila_salas.retain(|n| n != &nome_paciente);
-                recursos.historico_uso.registrar_inicio(&nome_paciente, &recursos.slots_salas, &recursos.historico_uso.sala).await;
+                for tipo in EQUIPE_CIRURGIA {
+                    estado.sair_fila(tipo, &nome_paciente);
+                }
                 progresso += 1.0;
                 estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
                 estado.registrar_atendimento(&nome_paciente, espera_sala);
-                estado.registrar_log(&format!("🔪 {} começou cirurgia (Médico + Sala)", nome_paciente));
+                estado.registrar_log(&format!("🔪 {} começou cirurgia (Médico + Sala + Enfermeiro)", nome_paciente));
             }
 
-            usar_recurso(&format!("Cirurgia - {}", nome_paciente), (4.0 * escala_tempo) as u64).await;
+            usar_recurso(&format!("Cirurgia - {}", nome_paciente), (4.0 * escala_tempo) as u64, &recursos.controle).await;
 
-            // Liberação da Sala e do Médico após a cirurgia
-            recursos.liberar_sala_slot(&nome_paciente).await;
-            drop(sala_permit);
-            
-            if let Some(medico) = medico_permit_op.take() {
-                recursos.liberar_medico_slot(&nome_paciente).await;
-                drop(medico);
+            // Liberação de toda a equipe após a cirurgia
+            for reserva in equipe {
+                reserva.devolver().await;
             }
             {
                 let mut estado = estado_gui.lock().await;
-                estado.registrar_log(&format!("✅ {} terminou cirurgia, liberou Sala e Médico", nome_paciente));
+                estado.registrar_log(&format!("✅ {} terminou cirurgia, liberou Sala, Médico e Enfermeiro", nome_paciente));
             }
 
             // --- ETAPA 4: Leito pós-cirurgia (Recuperação) ---
-            let inicio_leito = Instant::now();
+            self.transferir_se_preciso(&mut rede, LEITO, &mut recursos, &mut estado_gui, escala_tempo).await;
+            let inicio_leito = recursos.controle.agora();
             {
                 let mut estado = estado_gui.lock().await;
-                if self.prioridade == 1 {
-                    estado.fila_leitos.insert(0, nome_paciente.clone());
-                } else {
-                    estado.fila_leitos.push(nome_paciente.clone());
-                }
+                estado.entrar_fila(LEITO, &nome_paciente, self.sinais.critico());
                 estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
                 estado.registrar_log(&format!("🔹 {} entrou na fila de leitos (Recuperação)", nome_paciente));
             }
 
-            let leito_permit = match recursos.reservar_leito(nome_paciente.clone()).await {
-                Ok(permit) => permit,
-                Err(e) => {
-                    let mut estado = estado_gui.lock().await;
-                    estado.registrar_log(&format!("❌ {} Falha na reserva de leito (Exclusividade): {}", nome_paciente, e));
-                    return false;
-                }
+            let reserva_leito = match recursos.reservar(LEITO, nome_paciente.clone(), "Recuperação").await {
+                Ok(reserva) => reserva,
+                Err(e) => return self.abortar(&recursos, &estado_gui, &[LEITO], format!("Falha na reserva de leito: {}", e)).await,
             };
 
             {
-                let espera_leito = inicio_leito.elapsed();
+                let espera_leito = recursos.controle.decorrido(inicio_leito);
                 let mut estado = estado_gui.lock().await;
-                estado.fila_leitos.retain(|n| n != &nome_paciente);
-                recursos.historico_uso.registrar_inicio(&nome_paciente, &recursos.slots_leitos, &recursos.historico_uso.leito).await;
+                estado.sair_fila(LEITO, &nome_paciente);
                 progresso += 1.0;
                 estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
                 estado.registrar_atendimento(&nome_paciente, espera_leito);
                 estado.registrar_log(&format!("🛌 {} começou recuperação em leito", nome_paciente));
             }
 
-            usar_recurso(&format!("Leito - {}", nome_paciente), (3.0 * escala_tempo) as u64).await;
+            if self.ocupar_leito(&format!("Leito - {}", nome_paciente), (3.0 * escala_tempo) as u64, &recursos.controle).await {
+                estado_gui.lock().await.registrar_log(&format!("🏠 {} recebeu alta antecipada do leito", nome_paciente));
+            }
 
-            recursos.liberar_leito_slot(&nome_paciente).await;
-            drop(leito_permit);
+            reserva_leito.devolver().await;
         } else {
             // --- ETAPA 3: Leito (Observação/Sem Cirurgia) ---
-            let inicio_leito = Instant::now();
+            self.transferir_se_preciso(&mut rede, LEITO, &mut recursos, &mut estado_gui, escala_tempo).await;
+            let inicio_leito = recursos.controle.agora();
             {
                 let mut estado = estado_gui.lock().await;
-                if self.prioridade == 1 {
-                    estado.fila_leitos.insert(0, nome_paciente.clone());
-                } else {
-                    estado.fila_leitos.push(nome_paciente.clone());
-                }
+                estado.entrar_fila(LEITO, &nome_paciente, self.sinais.critico());
                 estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
                 estado.registrar_log(&format!("🔹 {} entrou na fila de leitos (Observação)", nome_paciente));
             }
 
-            let leito_permit = match recursos.reservar_leito(nome_paciente.clone()).await {
-                Ok(permit) => permit,
-                Err(e) => {
-                    let mut estado = estado_gui.lock().await;
-                    estado.registrar_log(&format!("❌ {} Falha na reserva de leito (Exclusividade): {}", nome_paciente, e));
-                    return false;
-                }
+            let reserva_leito = match recursos.reservar(LEITO, nome_paciente.clone(), "Observação").await {
+                Ok(reserva) => reserva,
+                Err(e) => return self.abortar(&recursos, &estado_gui, &[LEITO], format!("Falha na reserva de leito: {}", e)).await,
             };
             
+            porta_tratamento = recursos.controle.decorrido(inicio_atendimento);
             {
-                let espera_leito = inicio_leito.elapsed();
+                let espera_leito = recursos.controle.decorrido(inicio_leito);
                 let mut estado = estado_gui.lock().await;
-                estado.fila_leitos.retain(|n| n != &nome_paciente);
-                recursos.historico_uso.registrar_inicio(&nome_paciente, &recursos.slots_leitos, &recursos.historico_uso.leito).await;
+                estado.sair_fila(LEITO, &nome_paciente);
                 progresso += 1.0;
                 estado.atualizar_progresso(&nome_paciente, progresso / num_etapas as f32);
                 estado.registrar_atendimento(&nome_paciente, espera_leito);
                 estado.registrar_log(&format!("🛌 {} começou observação em leito", nome_paciente));
             }
 
-            usar_recurso(&format!("Leito - {}", nome_paciente), (3.0 * escala_tempo) as u64).await;
+            if self.ocupar_leito(&format!("Leito - {}", nome_paciente), (3.0 * escala_tempo) as u64, &recursos.controle).await {
+                estado_gui.lock().await.registrar_log(&format!("🏠 {} recebeu alta antecipada do leito", nome_paciente));
+            }
 
-            recursos.liberar_leito_slot(&nome_paciente).await;
-            drop(leito_permit);
+            reserva_leito.devolver().await;
         }
 
         // -------------------- ETAPA FINAL: Saída --------------------
 
-        let espera_total = inicio_atendimento.elapsed();
+        let espera_total = recursos.controle.decorrido(inicio_atendimento);
         {
             let mut estado = estado_gui.lock().await;
             estado.registrar_atendimento(&nome_paciente, espera_total);
             estado.registrar_log(&format!("🎉 {} concluiu atendimento.", nome_paciente));
         }
 
-        pausa((1.0 * escala_tempo) as u64).await;
+        pausa((1.0 * escala_tempo) as u64, &recursos.controle).await;
         {
             let mut estado = estado_gui.lock().await;
             estado.atualizar_progresso(&nome_paciente, 1.0);
@@ -281,6 +592,134 @@ impl Paciente {
         }
 
         // Se a função chegou a este ponto, o atendimento foi bem-sucedido.
-        true
+        ResultadoAtendimento::Concluido { porta_tratamento }
+    }
+
+    /// Percorre um trajeto fixo de cenário. Na aquisição incremental os recursos de cada etapa
+    /// são pedidos um a um, na ordem declarada, retendo os que já foram obtidos; é assim que
+    /// trajetos com ordens conflitantes produzem espera circular.
+    async fn percorrer_trajeto(
+        &self,
+        trajeto: &Trajeto,
+        recursos: Arc<Recursos>,
+        estado_gui: Arc<Mutex<EstadoRecursosGUI>>,
+        escala_tempo: f64,
+    ) -> ResultadoAtendimento {
+        let nome_paciente = self.nome.clone();
+        let num_etapas = trajeto.etapas.len().max(1) as f32;
+
+        recursos.controle.dormir(Duration::from_secs_f64(trajeto.chegada * escala_tempo)).await;
+        let inicio_atendimento = recursos.controle.agora();
+        let mut porta_tratamento = Duration::ZERO;
+        recursos.declarar_necessidades(&nome_paciente, trajeto.necessidades()).await;
+
+        // Reservas retidas entre as etapas
+        let mut mantidos: ReservasConjuntas = vec![];
+
+        for (i, etapa) in trajeto.etapas.iter().enumerate() {
+            let inicio_espera = recursos.controle.agora();
+            {
+                let mut estado = estado_gui.lock().await;
+                for tipo in &etapa.adquirir {
+                    estado.entrar_fila(tipo, &nome_paciente, self.sinais.critico());
+                }
+                estado.atualizar_progresso(&nome_paciente, i as f32 / num_etapas);
+                if !etapa.adquirir.is_empty() {
+                    estado.registrar_log(&format!(
+                        "🔹 {} aguarda {} para a etapa '{}' ({})",
+                        nome_paciente, etapa.adquirir.join(" + "), etapa.nome, trajeto.modo
+                    ));
+                }
+            }
+
+            let reserva = match trajeto.modo {
+                // Etapa que só usa o que já está retido
+                _ if etapa.adquirir.is_empty() => Ok(()),
+                ModoAquisicao::Incremental => {
+                    let mut reserva = Ok(());
+                    for tipo in &etapa.adquirir {
+                        match recursos.reservar(tipo, nome_paciente.clone(), etapa.nome).await {
+                            Ok(reserva) => mantidos.push(reserva),
+                            Err(e) => {
+                                reserva = Err(e);
+                                break;
+                            }
+                        }
+                    }
+                    reserva
+                }
+                ModoAquisicao::Atomica => {
+                    // Sem hold-and-wait: devolve o que mantém e pede tudo de novo, de uma só vez
+                    let mut tipos: Vec<TipoRecurso> = mantidos.iter().map(ReservaRecurso::tipo).collect();
+                    tipos.extend(&etapa.adquirir);
+                    for reserva in mantidos.drain(..) {
+                        reserva.devolver().await;
+                    }
+                    recursos
+                        .reservar_conjunto(&tipos, nome_paciente.clone(), etapa.nome)
+                        .await
+                        .map(|equipe| mantidos.extend(equipe))
+                }
+            };
+
+            if let Err(e) = reserva {
+                // Interrompido (ex.: preemptado pelo monitor): devolve tudo o que mantinha
+                drop(mantidos);
+                recursos.liberar_todos(&nome_paciente).await;
+                let mut estado = estado_gui.lock().await;
+                for tipo in &etapa.adquirir {
+                    estado.sair_fila(tipo, &nome_paciente);
+                }
+                estado.atualizar_progresso(&nome_paciente, 1.0);
+                estado.registrar_log(&format!("❌ {} Falha na etapa '{}': {}", nome_paciente, etapa.nome, e));
+                return ResultadoAtendimento::Abortado;
+            }
+
+            // A última etapa é o tratamento definitivo
+            if i + 1 == trajeto.etapas.len() {
+                porta_tratamento = recursos.controle.decorrido(inicio_atendimento);
+            }
+            {
+                let mut estado = estado_gui.lock().await;
+                for tipo in &etapa.adquirir {
+                    estado.sair_fila(tipo, &nome_paciente);
+                }
+                estado.registrar_atendimento(&nome_paciente, recursos.controle.decorrido(inicio_espera));
+                let retidos: Vec<TipoRecurso> = mantidos.iter().map(ReservaRecurso::tipo).collect();
+                estado.registrar_log(&format!(
+                    "✅ {} iniciou a etapa '{}' com {}",
+                    nome_paciente, etapa.nome, retidos.join(" + ")
+                ));
+            }
+
+            let descricao = format!("{} - {}", etapa.nome, nome_paciente);
+            let segundos = (etapa.duracao * escala_tempo) as u64;
+            if !mantidos.iter().any(|r| r.tipo() == LEITO) {
+                usar_recurso(&descricao, segundos, &recursos.controle).await;
+            } else if self.ocupar_leito(&descricao, segundos, &recursos.controle).await {
+                estado_gui.lock().await.registrar_log(&format!("🏠 {} recebeu alta antecipada do leito", nome_paciente));
+            }
+
+            for tipo in &etapa.liberar {
+                if let Some(pos) = mantidos.iter().position(|r| r.tipo() == *tipo) {
+                    mantidos.remove(pos).devolver().await;
+                }
+            }
+        }
+
+        // -------------------- Saída: devolve o que ainda estiver retido --------------------
+        for reserva in mantidos {
+            reserva.devolver().await;
+        }
+        recursos.liberar_todos(&nome_paciente).await;
+
+        {
+            let mut estado = estado_gui.lock().await;
+            estado.registrar_atendimento(&nome_paciente, recursos.controle.decorrido(inicio_atendimento));
+            estado.atualizar_progresso(&nome_paciente, 1.0);
+            estado.registrar_log(&format!("🎉 {} concluiu o trajeto.", nome_paciente));
+        }
+
+        ResultadoAtendimento::Concluido { porta_tratamento }
     }
 }
@@ -0,0 +1,92 @@
+// painel_web.rs
+use std::sync::Arc;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{interval, Duration};
+use tokio_tungstenite::tungstenite::Message;
+use crate::http;
+use crate::simulacao::Simulacao;
+
+/// Página do painel, embutida no binário.
+const PAGINA_PAINEL: &str = include_str!("painel_web.html");
+/// Intervalo entre dois envios de estado (o mesmo ciclo do monitor).
+const INTERVALO_PAINEL: Duration = Duration::from_millis(500);
+/// Quantos eventos recentes de cada hospital vão em cada envio.
+const EVENTOS_PAINEL: usize = 40;
+
+/// Fotografia enviada ao navegador: o mesmo conteúdo do `MonitorGUI` para cada hospital da rede
+/// (slots, filas, progresso, eventos recentes) e as transferências entre eles.
+pub async fn retrato_painel(simulacao: &Simulacao) -> Value {
+    let rede = &simulacao.rede;
+
+    let mut hospitais = vec![];
+    for (i, h) in rede.hospitais.iter().enumerate() {
+        let mut estado = h.estado.lock().await;
+        estado.sincronizar(&h.recursos).await;
+        let inicio_eventos = estado.logs.len().saturating_sub(EVENTOS_PAINEL);
+        hospitais.push(json!({
+            "indice": i,
+            "nome": h.nome,
+            "estado": &*estado,
+            "eventos": &estado.logs[inicio_eventos..],
+        }));
+    }
+
+    json!({
+        "pausado": simulacao.controle.pausado(),
+        "hospitais": hospitais,
+        "transferencias": &*rede.transferencias.lock().await,
+    })
+}
+
+/// Serve o painel em `http://<endereco>/` e o fluxo de estado em `ws://<endereco>/ws`.
+pub async fn iniciar_painel_web(simulacao: Arc<Simulacao>, endereco: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(endereco).await?;
+    println!("🖥️ Painel web em http://{}/", endereco);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(atender_conexao(stream, simulacao.clone()));
+    }
+}
+
+async fn atender_conexao(mut stream: TcpStream, simulacao: Arc<Simulacao>) {
+    // Espia a linha de requisição sem consumi-la: o handshake do WebSocket precisa dela inteira
+    let Some(espiada) = http::espiar_requisicao(&stream).await else { return };
+    if (espiada.metodo.as_str(), espiada.caminho.as_str()) == ("GET", "/ws") {
+        transmitir_estado(stream, simulacao).await;
+        return;
+    }
+
+    let Some(requisicao) = http::ler_requisicao(&mut stream).await else { return };
+    let _ = match (requisicao.metodo.as_str(), requisicao.caminho.as_str()) {
+        ("GET", "/") | ("GET", "/index.html") => {
+            http::responder(&mut stream, "200 OK", "text/html; charset=utf-8", PAGINA_PAINEL.as_bytes()).await
+        }
+        _ => http::responder(&mut stream, "404 Not Found", "text/plain; charset=utf-8", b"use / ou /ws\n").await,
+    };
+}
+
+/// Envia o retrato do painel a cada ciclo até o navegador fechar a conexão.
+async fn transmitir_estado(stream: TcpStream, simulacao: Arc<Simulacao>) {
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else { return };
+    let (mut envio, mut recebimento) = ws.split();
+    let mut ciclo = interval(INTERVALO_PAINEL);
+
+    loop {
+        tokio::select! {
+            _ = ciclo.tick() => {
+                let retrato = retrato_painel(&simulacao).await;
+                if envio.send(Message::Text(retrato.to_string())).await.is_err() {
+                    break;
+                }
+            }
+            mensagem = recebimento.next() => match mensagem {
+                // O painel é só leitura: mensagens do navegador são ignoradas
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
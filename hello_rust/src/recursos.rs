@@ -1,21 +1,66 @@
 // recursos.rs
-use std::sync::Arc;
-use tokio::sync::{Semaphore, OwnedSemaphorePermit, Mutex};
-use tokio::time::{Duration, timeout, sleep, Instant};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use serde::Serialize;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{Semaphore, OwnedSemaphorePermit, Mutex, Notify};
+use tokio::time::{Duration, timeout, sleep};
+use crate::deadlock::{self, RetratoAlocacao};
+use crate::controle::ControleSimulacao;
+use crate::cancelamento::{Cancelamento, MotivoCancelamento};
 
 // ---------------- Funções Utilitárias ----------------
 
-/// Simula o uso de um recurso por um determinado tempo (com log)
-pub async fn usar_recurso(nome: &str, duracao: u64) {
+/// Simula o uso de um recurso por um determinado tempo (com log). O tempo não corre com a simulação pausada.
+pub async fn usar_recurso(nome: &str, duracao: u64, controle: &ControleSimulacao) {
     println!("🔧 Utilizando recurso: {} por {} segundos...", nome, duracao);
-    sleep(Duration::from_secs(duracao)).await;
+    controle.dormir(Duration::from_secs(duracao)).await;
     println!("🏁 Liberação do recurso: {}", nome);
 }
 
-/// Função auxiliar para pausar a execução entre etapas (para logs visíveis)
-#[allow(dead_code)]
-pub async fn pausa(segundos: u64) {
-    sleep(Duration::from_secs(segundos)).await;
+/// Função auxiliar para pausar a execução entre etapas (para logs visíveis), em tempo simulado
+pub async fn pausa(segundos: u64, controle: &ControleSimulacao) {
+    controle.dormir(Duration::from_secs(segundos)).await;
+}
+
+// ---------------- Configuração dos Tipos de Recurso ----------------
+
+/// Identificador de um tipo de recurso (a chave usada na configuração).
+pub type TipoRecurso = &'static str;
+
+pub const MEDICO: TipoRecurso = "medico";
+pub const SALA: TipoRecurso = "sala";
+pub const LEITO: TipoRecurso = "leito";
+pub const EXAME: TipoRecurso = "exame";
+pub const ENFERMEIRO: TipoRecurso = "enfermeiro";
+
+/// Tempo de espera (em segundos) a partir do qual um paciente entra na lista de deadlock.
+const TIMEOUT_ALERTA_SECS: u64 = 10;
+
+/// Reservas obtidas de uma vez numa reserva conjunta, na ordem dos tipos pedidos.
+pub type ReservasConjuntas = Vec<ReservaRecurso>;
+
+/// Declaração de um tipo de recurso. Cada entrada da configuração vira um
+/// `PoolRecurso` com semáforo, slots, filas, histórico e coluna na GUI.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ConfigRecurso {
+    pub tipo: TipoRecurso,
+    /// Nome no plural, usado em títulos e logs (ex.: "Médicos").
+    pub nome: &'static str,
+    /// Nome de uma instância (ex.: "Médico" → "Médico 2").
+    pub instancia: &'static str,
+    pub icone: &'static str,
+    pub capacidade: usize,
+    /// Posição na ordem global de aquisição (prevenção de espera circular).
+    pub ordem: u32,
+}
+
+impl ConfigRecurso {
+    /// Título exibido na GUI (ex.: "👨‍⚕️ Médicos").
+    pub fn titulo(&self) -> String {
+        format!("{} {}", self.icone, self.nome)
+    }
 }
 
 // ---------------- Estruturas de Rastreamento de Uso ----------------
@@ -23,138 +68,570 @@ pub async fn pausa(segundos: u64) {
 #[derive(Debug, Clone)]
 pub struct EventoUso {
     pub nome_paciente: String,
+    /// Etapa do caminho clínico em que o recurso foi usado (ex.: "Consulta", "Cirurgia").
+    pub etapa: String,
     pub inicio: f64,
     pub fim: f64,
     pub instancia_id: usize,
 }
 
+/// Intervalo em que um paciente aguardou na fila de um recurso (`fim` é 0.0 enquanto espera).
+#[derive(Debug, Clone)]
+pub struct EventoEspera {
+    pub nome_paciente: String,
+    pub tipo: TipoRecurso,
+    pub etapa: String,
+    pub inicio: f64,
+    pub fim: f64,
+}
+
 pub struct HistoricoUso {
-    pub inicio_simulacao: Instant,
-    pub medico: Mutex<Vec<EventoUso>>,
-    pub sala: Mutex<Vec<EventoUso>>,
-    pub leito: Mutex<Vec<EventoUso>>,
-    pub exame: Mutex<Vec<EventoUso>>, // NOVO: Histórico para Exames
+    /// Relógio da simulação: os tempos dos eventos são em tempo simulado (segundos).
+    pub controle: Arc<ControleSimulacao>,
+    /// Eventos de uso por tipo de recurso, na ordem da configuração.
+    pub eventos: Vec<(TipoRecurso, Mutex<Vec<EventoUso>>)>,
+    /// Esperas em fila de todos os recursos, na ordem de entrada.
+    pub esperas: StdMutex<Vec<EventoEspera>>,
+}
+
+/// Espera em andamento: encerrada no histórico ao ser descartada (reserva concedida,
+/// desistência, falha ou atendimento cancelado).
+struct EsperaNaFila<'a> {
+    historico: &'a HistoricoUso,
+    indice: usize,
+}
+
+impl Drop for EsperaNaFila<'_> {
+    fn drop(&mut self) {
+        let agora = self.historico.controle.agora().as_secs_f64();
+        if let Some(espera) = self.historico.esperas.lock().unwrap().get_mut(self.indice) {
+            espera.fim = agora;
+        }
+    }
 }
 
 impl HistoricoUso {
-    pub fn new() -> Self {
+    pub fn new(tipos: &[TipoRecurso], controle: Arc<ControleSimulacao>) -> Self {
         Self {
-            inicio_simulacao: Instant::now(),
-            medico: Mutex::new(vec![]),
-            sala: Mutex::new(vec![]),
-            leito: Mutex::new(vec![]),
-            exame: Mutex::new(vec![]), // NOVO: Inicialização do histórico
+            controle,
+            eventos: tipos.iter().map(|tipo| (*tipo, Mutex::new(vec![]))).collect(),
+            esperas: StdMutex::new(vec![]),
         }
     }
 
-    // O método agora recebe o Mutex dos slots para atualização
-    pub async fn registrar_inicio(&self, nome: &str, slots_mutex: &Arc<Mutex<Vec<Option<String>>>>, historico: &Mutex<Vec<EventoUso>>) -> Option<usize> {
-        let now = self.inicio_simulacao.elapsed().as_secs_f64();
-        let mut slots = slots_mutex.lock().await;
+    /// Registra a entrada do paciente na fila de `tipo`; a espera termina quando o retorno é descartado.
+    fn registrar_espera(&self, nome: &str, tipo: TipoRecurso, etapa: &str) -> EsperaNaFila<'_> {
+        let mut esperas = self.esperas.lock().unwrap();
+        esperas.push(EventoEspera {
+            nome_paciente: nome.to_string(),
+            tipo,
+            etapa: etapa.to_string(),
+            inicio: self.controle.agora().as_secs_f64(),
+            fim: 0.0,
+        });
+        EsperaNaFila { historico: self, indice: esperas.len() - 1 }
+    }
+
+    /// Retorna o histórico de eventos de um tipo de recurso.
+    pub fn eventos(&self, tipo: &str) -> Option<&Mutex<Vec<EventoUso>>> {
+        self.eventos.iter().find(|(t, _)| *t == tipo).map(|(_, eventos)| eventos)
+    }
+
+    /// Ocupa o primeiro slot livre do pool e abre o uso no histórico, com os slots travados
+    /// até o fim: quem lê slots e eventos nunca vê um sem o outro. Devolve os índices do slot
+    /// e do `EventoUso` (usados pela `ReservaRecurso` para encerrar exatamente esse uso).
+    async fn registrar_inicio(&self, nome: &str, etapa: &str, pool: &PoolRecurso) -> Option<(usize, usize)> {
+        let now = self.controle.agora().as_secs_f64();
+        let mut slots = pool.slots.lock().await;
+        let mut historico = self.eventos(pool.config.tipo)?.lock().await;
 
+        // Nenhuma espera daqui em diante: cancelado antes deste ponto, nada foi registrado
         for (i, slot) in slots.iter_mut().enumerate() {
             if slot.is_none() {
                 *slot = Some(nome.to_string());
-                
-                historico.lock().await.push(EventoUso {
+                historico.push(EventoUso {
                     nome_paciente: nome.to_string(),
+                    etapa: etapa.to_string(),
                     inicio: now,
                     fim: 0.0,
                     instancia_id: i + 1,
                 });
-                return Some(i + 1);
+                return Some((i, historico.len() - 1));
             }
         }
         None
     }
+}
 
-    // O método agora recebe o Mutex dos slots para atualização
-    pub async fn registrar_fim(&self, nome: &str, slots_mutex: &Arc<Mutex<Vec<Option<String>>>>, historico: &Mutex<Vec<EventoUso>>) {
-        let now = self.inicio_simulacao.elapsed().as_secs_f64();
-        let mut hist_lock = historico.lock().await;
-        
-        // Atualiza o histórico de uso (registro de tempo final)
-        if let Some(evento) = hist_lock.iter_mut().rev().find(|e| e.nome_paciente == nome && e.fim == 0.0) {
-            evento.fim = now;
-        }
-
-        // Libera o slot de uso
-        let mut slots = slots_mutex.lock().await;
-        for slot in slots.iter_mut() {
-            if let Some(paciente_nome) = slot {
-                if paciente_nome == nome {
-                    *slot = None; 
-                    return;
+// ---------------- Reserva de uma Instância (guarda RAII) ----------------
+
+/// Posse de cada paciente: tipos de recurso mantidos (um por instância reservada).
+type Posse = HashMap<String, Vec<TipoRecurso>>;
+
+/// O que uma reserva registrou ao ser concedida: slot ocupado, uso aberto no histórico e posse.
+#[derive(Clone)]
+struct Ocupacao {
+    tipo: TipoRecurso,
+    nome: String,
+    /// Índices do slot e do `EventoUso` (ausentes se não havia slot livre para registrar).
+    slot: Option<(usize, usize)>,
+    slots: Arc<Mutex<Vec<Option<String>>>>,
+    capacidade: Arc<AtomicUsize>,
+    reducao_pendente: Arc<AtomicUsize>,
+    historico: Arc<HistoricoUso>,
+    posse: Arc<Mutex<Posse>>,
+}
+
+impl Ocupacao {
+    /// Encerra o uso no histórico, libera o slot e tira uma instância do tipo da posse do paciente.
+    fn encerrar(&self, slots: &mut Vec<Option<String>>, eventos: Option<&mut Vec<EventoUso>>, posse: &mut Posse) {
+        if let Some((instancia, evento)) = self.slot {
+            match eventos.and_then(|eventos| eventos.get_mut(evento)) {
+                // Já encerrado (ex.: instância devolvida antes por outro caminho): nada mais a fazer
+                Some(evento) if evento.fim != 0.0 => return,
+                Some(evento) => evento.fim = self.historico.controle.agora().as_secs_f64(),
+                None => {}
+            }
+            if slots.get(instancia).is_some_and(|slot| slot.as_deref() == Some(self.nome.as_str())) {
+                slots[instancia] = None;
+            }
+            // Uma instância retirada enquanto ocupada só some quando o slot do fim fica livre
+            aparar_slots(slots, &self.capacidade, &self.reducao_pendente);
+        }
+
+        if let Some(mantidos) = posse.get_mut(&self.nome) {
+            if let Some(i) = mantidos.iter().position(|t| *t == self.tipo) {
+                mantidos.remove(i);
+            }
+            if mantidos.is_empty() {
+                posse.remove(&self.nome);
+            }
+        }
+    }
+
+    /// Encerra sem esperar; devolve `false` (sem alterar nada) se algum registro estiver travado.
+    fn encerrar_agora(&self) -> bool {
+        let (Ok(mut slots), Ok(mut posse)) = (self.slots.try_lock(), self.posse.try_lock()) else {
+            return false;
+        };
+        match self.historico.eventos(self.tipo).map(Mutex::try_lock) {
+            Some(Ok(mut eventos)) => self.encerrar(&mut slots, Some(&mut eventos), &mut posse),
+            Some(Err(_)) => return false,
+            None => self.encerrar(&mut slots, None, &mut posse),
+        }
+        true
+    }
+
+    /// Encerra esperando os registros travados (mesma ordem de travas de `registrar_inicio`).
+    async fn encerrar_esperando(&self) {
+        let mut slots = self.slots.lock().await;
+        let mut eventos = match self.historico.eventos(self.tipo) {
+            Some(eventos) => Some(eventos.lock().await),
+            None => None,
+        };
+        let mut posse = self.posse.lock().await;
+        self.encerrar(&mut slots, eventos.as_deref_mut(), &mut posse);
+    }
+
+    /// Encerra bloqueando a thread até os registros serem liberados. Só fora de um runtime do
+    /// tokio (dentro dele, `blocking_lock` entra em pânico): lá se usa `encerrar_esperando`.
+    fn encerrar_bloqueando(&self) {
+        let mut slots = self.slots.blocking_lock();
+        let mut eventos = self.historico.eventos(self.tipo).map(Mutex::blocking_lock);
+        let mut posse = self.posse.blocking_lock();
+        self.encerrar(&mut slots, eventos.as_deref_mut(), &mut posse);
+    }
+}
+
+/// Instância de um recurso reservada para um paciente: guarda a permissão do semáforo, o slot
+/// ocupado e o uso aberto no histórico. Ao ser descartada (fim da etapa, falha, atendimento
+/// cancelado ou pânico da tarefa) encerra o uso, libera o slot e só então devolve a permissão.
+pub struct ReservaRecurso {
+    ocupacao: Ocupacao,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl ReservaRecurso {
+    pub fn tipo(&self) -> TipoRecurso {
+        self.ocupacao.tipo
+    }
+
+    /// Devolve a instância no fluxo normal do atendimento. É o mesmo que descartar a reserva,
+    /// mas espera os registros travados em vez de adiar a liberação para outra tarefa.
+    /// Cancelada durante a espera, nada foi alterado ainda e o descarte faz a liberação.
+    pub async fn devolver(mut self) {
+        self.ocupacao.encerrar_esperando().await;
+        drop(self.permit.take());
+    }
+}
+
+impl Drop for ReservaRecurso {
+    fn drop(&mut self) {
+        let Some(permit) = self.permit.take() else { return };
+        if self.ocupacao.encerrar_agora() {
+            return;
+        }
+        match tokio::runtime::Handle::try_current() {
+            // Algum registro está travado: a liberação fica para uma tarefa, que segura a permissão até lá
+            Ok(runtime) => {
+                let ocupacao = self.ocupacao.clone();
+                runtime.spawn(async move {
+                    ocupacao.encerrar_esperando().await;
+                    drop(permit);
+                });
+            }
+            // Fora do runtime não há tarefa para onde adiar: espera os registros aqui mesmo
+            Err(_) => {
+                self.ocupacao.encerrar_bloqueando();
+                drop(permit);
+            }
+        }
+    }
+}
+
+// ---------------- Desistência de Pacientes (Reneging/Balking) ----------------
+
+/// Limites de paciência de um paciente enquanto aguarda um recurso.
+/// Os campos em `None` significam paciência ilimitada (comportamento original).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Paciencia {
+    /// Tempo máximo na fila antes de desistir (reneging).
+    pub espera_max: Option<Duration>,
+    /// Tamanho de fila a partir do qual o paciente se recusa a entrar (balking).
+    pub fila_max: Option<usize>,
+}
+
+/// Motivo pelo qual uma reserva de recurso não foi concedida.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FalhaReserva {
+    /// O paciente já ocupa um slot deste tipo de recurso.
+    Exclusividade(String),
+    /// O paciente se recusou a entrar na fila por estar longa demais (balking).
+    FilaLonga { tamanho: usize },
+    /// O paciente desistiu após esperar além da sua paciência (reneging).
+    PacienciaEsgotada { espera: Duration },
+    /// O tipo de recurso pedido não existe na configuração.
+    RecursoInexistente(String),
+    /// O pedido desrespeitou a ordem global dos recursos e a política é `Rejeitar`.
+    ViolacaoOrdem(ViolacaoOrdem),
+    /// O monitor escolheu o paciente como vítima para quebrar um deadlock.
+    Preemptado,
+}
+
+impl FalhaReserva {
+    /// Indica se a falha foi uma desistência do próprio paciente.
+    pub fn eh_desistencia(&self) -> bool {
+        matches!(self, FalhaReserva::FilaLonga { .. } | FalhaReserva::PacienciaEsgotada { .. })
+    }
+}
+
+impl fmt::Display for FalhaReserva {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FalhaReserva::Exclusividade(msg) => write!(f, "{}", msg),
+            FalhaReserva::FilaLonga { tamanho } => {
+                write!(f, "recusou-se a entrar na fila ({} pacientes aguardando)", tamanho)
+            }
+            FalhaReserva::PacienciaEsgotada { espera } => {
+                write!(f, "desistiu após {:.1}s de espera", espera.as_secs_f64())
+            }
+            FalhaReserva::RecursoInexistente(tipo) => write!(f, "recurso '{}' não configurado", tipo),
+            FalhaReserva::ViolacaoOrdem(violacao) => write!(f, "{}", violacao),
+            FalhaReserva::Preemptado => write!(f, "preemptado pelo monitor para quebrar um deadlock"),
+        }
+    }
+}
+
+// ---------------- Ordenação Global (Prevenção de Espera Circular) ----------------
+
+/// Política aplicada quando um paciente pede um recurso de ordem menor que outro que já mantém.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PoliticaOrdem {
+    /// Nenhuma verificação (comportamento original).
+    #[default]
+    Desligada,
+    /// Registra a violação, mas concede o recurso normalmente.
+    Reportar,
+    /// Registra a violação e nega o pedido.
+    Rejeitar,
+}
+
+/// Pedido feito fora da ordem global, com o que o paciente mantinha no momento.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViolacaoOrdem {
+    pub paciente: String,
+    pub etapa: String,
+    /// Tipo pedido e sua ordem.
+    pub solicitado: (TipoRecurso, u32),
+    /// Tipos mantidos pelo paciente e suas ordens.
+    pub mantidos: Vec<(TipoRecurso, u32)>,
+    pub rejeitada: bool,
+}
+
+impl fmt::Display for ViolacaoOrdem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mantidos: Vec<String> = self.mantidos.iter().map(|(tipo, ordem)| format!("{}#{}", tipo, ordem)).collect();
+        write!(
+            f,
+            "{} pediu {}#{} na etapa '{}' mantendo [{}] ({})",
+            self.paciente,
+            self.solicitado.0,
+            self.solicitado.1,
+            self.etapa,
+            mantidos.join(", "),
+            if self.rejeitada { "pedido REJEITADO" } else { "apenas reportado" }
+        )
+    }
+}
+
+/// Ciclo de espera confirmado ou preempção realizada, com o tempo simulado em que ocorreu.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OcorrenciaDeadlock {
+    /// Espera circular detectada pelo monitor entre estes pacientes.
+    Ciclo { tempo: f64, pacientes: Vec<String> },
+    /// Paciente preemptado; `origem` indica quem pediu (monitor de deadlock, API de controle).
+    Preempcao { tempo: f64, paciente: String, origem: String },
+}
+
+// ---------------- POOL DE RECURSO (um por tipo configurado) ----------------
+
+/// Conjunto de instâncias de um tipo de recurso: semáforo de permissões,
+/// slots de uso lidos pela GUI, fila de espera e lista de suspeitos de deadlock.
+pub struct PoolRecurso {
+    pub config: ConfigRecurso,
+    /// Capacidade atual (começa em `config.capacidade` e pode ser alterada em execução).
+    capacidade: Arc<AtomicUsize>,
+    /// Instâncias que ainda precisam ser retiradas por uma redução de capacidade.
+    reducao_pendente: Arc<AtomicUsize>,
+    pub semaforo: Arc<Semaphore>,
+    pub slots: Arc<Mutex<Vec<Option<String>>>>,
+    pub fila: Arc<Mutex<Vec<String>>>,
+    pub deadlock: Arc<Mutex<Vec<String>>>,
+    /// Reservas simples de pacientes com preferência aguardando este pool, na ordem de chegada.
+    vez: StdMutex<Vec<String>>,
+}
+
+/// Lugar do paciente entre as reservas com preferência de um pool. Ao ser descartado (reserva
+/// concedida, desistência ou atendimento cancelado) o paciente sai da vez.
+struct LugarNaVez<'a> {
+    pool: &'a PoolRecurso,
+    nome: &'a str,
+}
+
+impl Drop for LugarNaVez<'_> {
+    fn drop(&mut self) {
+        self.pool.vez.lock().unwrap().retain(|n| n != self.nome);
+    }
+}
+
+/// Posição em que o paciente entra numa fila: quem tem preferência fica atrás apenas
+/// dos outros preferenciais; os demais vão para o fim.
+fn lugar_na_fila(fila: &[String], nome: &str, preferenciais: &HashSet<String>) -> usize {
+    if preferenciais.contains(nome) {
+        fila.iter().take_while(|n| preferenciais.contains(*n)).count()
+    } else {
+        fila.len()
+    }
+}
+
+impl PoolRecurso {
+    pub fn novo(config: ConfigRecurso) -> Self {
+        Self {
+            config,
+            capacidade: Arc::new(AtomicUsize::new(config.capacidade)),
+            reducao_pendente: Arc::new(AtomicUsize::new(0)),
+            semaforo: Arc::new(Semaphore::new(config.capacidade)),
+            slots: Arc::new(Mutex::new(vec![None; config.capacidade])),
+            fila: Arc::new(Mutex::new(vec![])),
+            deadlock: Arc::new(Mutex::new(vec![])),
+            vez: StdMutex::new(vec![]),
+        }
+    }
+
+    /// Atualiza o lugar do paciente entre as reservas com preferência e retorna quantas
+    /// dessas reservas estão à frente dele (todas, se ele não tiver preferência).
+    fn a_frente(&self, nome: &str, preferencial: bool) -> usize {
+        let mut vez = self.vez.lock().unwrap();
+        let posicao = vez.iter().position(|n| n == nome);
+        match (preferencial, posicao) {
+            (true, Some(i)) => i,
+            (true, None) => {
+                vez.push(nome.to_string());
+                vez.len() - 1
+            }
+            (false, Some(i)) => {
+                vez.remove(i);
+                vez.len()
+            }
+            (false, None) => vez.len(),
+        }
+    }
+
+    pub fn capacidade(&self) -> usize {
+        self.capacidade.load(Ordering::SeqCst)
+    }
+
+    /// Instâncias ocupadas que ainda serão retiradas por uma redução de capacidade.
+    pub fn reducao_pendente(&self) -> usize {
+        self.reducao_pendente.load(Ordering::SeqCst)
+    }
+
+    /// Altera a capacidade do pool. Aumentos valem na hora (cancelando antes reduções pendentes);
+    /// reduções retiram as instâncias livres imediatamente e as ocupadas à medida que forem devolvidas.
+    pub async fn alterar_capacidade(&self, nova: usize) {
+        // Capacidade e reduções pendentes só mudam com os slots travados (ver `aparar_slots`)
+        let mut slots = self.slots.lock().await;
+        let atual = self.capacidade.swap(nova, Ordering::SeqCst);
+
+        if nova > atual {
+            let aumento = nova - atual;
+            let canceladas = self
+                .reducao_pendente
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |p| Some(p - p.min(aumento)))
+                .map_or(0, |pendente| pendente.min(aumento));
+            // Os slots novos existem antes das permissões: quem acordar já encontra um slot livre
+            let instancias = nova + self.reducao_pendente.load(Ordering::SeqCst);
+            if slots.len() < instancias {
+                slots.resize(instancias, None);
+            }
+            self.semaforo.add_permits(aumento - canceladas);
+        } else {
+            for _ in nova..atual {
+                self.reducao_pendente.fetch_add(1, Ordering::SeqCst);
+                match self.semaforo.clone().try_acquire_owned() {
+                    Ok(permit) => Self::retirar_instancia(permit, &self.capacidade, &self.reducao_pendente, &mut slots),
+                    Err(_) => {
+                        // Instância ocupada: retirada assim que for devolvida
+                        let (semaforo, capacidade, pendente, slots) =
+                            (self.semaforo.clone(), self.capacidade.clone(), self.reducao_pendente.clone(), self.slots.clone());
+                        tokio::spawn(async move {
+                            if let Ok(permit) = semaforo.acquire_owned().await {
+                                Self::retirar_instancia(permit, &capacidade, &pendente, &mut *slots.lock().await);
+                            }
+                        });
+                    }
                 }
             }
         }
     }
+
+    /// Descarta definitivamente a permissão, se a redução ainda estiver pendente (um aumento
+    /// posterior pode tê-la cancelado; nesse caso a permissão é devolvida), e apara os slots.
+    fn retirar_instancia(permit: OwnedSemaphorePermit, capacidade: &AtomicUsize, pendente: &AtomicUsize, slots: &mut Vec<Option<String>>) {
+        if pendente.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |p| p.checked_sub(1)).is_err() {
+            return;
+        }
+        permit.forget();
+        aparar_slots(slots, capacidade, pendente);
+    }
+}
+
+/// Remove do fim os slots livres que sobram das instâncias existentes (capacidade mais as
+/// retiradas pendentes, cada uma ainda com permissão no semáforo). Só o fim encolhe: o índice
+/// de cada slot, que é o `instancia_id` do histórico, nunca muda enquanto ele existe. Quem
+/// ganha uma permissão sempre acha um slot livre, pois há ao menos um slot por permissão.
+fn aparar_slots(slots: &mut Vec<Option<String>>, capacidade: &AtomicUsize, pendente: &AtomicUsize) {
+    let instancias = capacidade.load(Ordering::SeqCst) + pendente.load(Ordering::SeqCst);
+    while slots.len() > instancias && slots.last().is_some_and(Option::is_none) {
+        slots.pop();
+    }
 }
 
 // ---------------- ESTRUTURA RECURSOS (com lógica de Deadlock) ----------------
 
 pub struct Recursos {
-    pub medicos: Arc<Semaphore>,
-    pub salas_cirurgia: Arc<Semaphore>,
-    pub leitos: Arc<Semaphore>,
-    pub equipamentos_exames: Arc<Semaphore>, // NOVO: Equipamentos de Exame
-
-    // Slots de uso que a GUI irá ler
-    pub slots_medicos: Arc<Mutex<Vec<Option<String>>>>,
-    pub slots_salas: Arc<Mutex<Vec<Option<String>>>>,
-    pub slots_leitos: Arc<Mutex<Vec<Option<String>>>>,
-    pub slots_exames: Arc<Mutex<Vec<Option<String>>>>, // NOVO: Slots de Exames
-
-    pub fila_medicos: Arc<Mutex<Vec<String>>>,
-    pub fila_salas: Arc<Mutex<Vec<String>>>,
-    pub fila_leitos: Arc<Mutex<Vec<String>>>,
-    pub fila_exames: Arc<Mutex<Vec<String>>>, // NOVO: Fila de Exames
-
-    pub deadlock_medicos: Arc<Mutex<Vec<String>>>,
-    pub deadlock_salas: Arc<Mutex<Vec<String>>>,
-    pub deadlock_leitos: Arc<Mutex<Vec<String>>>,
-    pub deadlock_exames: Arc<Mutex<Vec<String>>>, // NOVO: Deadlock de Exames
+    /// Um pool por tipo de recurso, na ordem da configuração.
+    pub pools: Vec<PoolRecurso>,
     pub historico_uso: Arc<HistoricoUso>, 
+
+    pub politica_ordem: PoliticaOrdem,
+    /// Tipos de recurso mantidos por cada paciente (usado na verificação da ordem global).
+    pub posse: Arc<Mutex<Posse>>,
+    pub violacoes_ordem: Mutex<Vec<ViolacaoOrdem>>,
+
+    /// Pacientes escolhidos como vítima pelo monitor; a próxima tentativa de reserva deles falha.
+    pub preemptados: Mutex<HashSet<String>>,
+    /// Alças de cancelamento dos pacientes em atendimento (a preempção cancela o atendimento todo).
+    cancelamentos: Mutex<HashMap<String, Cancelamento>>,
+    /// Total de preempções realizadas (monitor e API de controle).
+    pub preempcoes: AtomicUsize,
+    /// Ciclos detectados e preempções, na ordem em que aconteceram (relatório do fim da execução).
+    pub ocorrencias_deadlock: Mutex<Vec<OcorrenciaDeadlock>>,
+    /// Pacientes com preferência nas filas (críticos e os passados à frente pelo operador).
+    pub preferenciais: Mutex<HashSet<String>>,
+    /// Evitação de deadlock pelo algoritmo do banqueiro (só concede pedidos que mantêm o estado seguro).
+    pub banqueiro: bool,
+    /// Necessidade máxima declarada por cada paciente (usada pelo algoritmo do banqueiro).
+    pub necessidades: Mutex<HashMap<String, Vec<TipoRecurso>>>,
+    /// Serializa as decisões do banqueiro (verificação de segurança + concessão).
+    decisao_alocacao: Mutex<()>,
+    /// Acorda as reservas bloqueadas no semáforo quando alguém ganha ou perde a preferência.
+    preferencia_alterada: Notify,
+
+    /// Pausa/retomada da simulação (compartilhado entre os hospitais da rede).
+    pub controle: Arc<ControleSimulacao>,
 }
 
 impl Recursos {
-    pub fn novo(qtd_medicos: usize, qtd_salas: usize, qtd_leitos: usize, qtd_exames: usize) -> Self {
+    pub fn novo(config: &[ConfigRecurso]) -> Self {
+        let tipos: Vec<TipoRecurso> = config.iter().map(|c| c.tipo).collect();
+        let controle = Arc::new(ControleSimulacao::novo());
         Self {
-            medicos: Arc::new(Semaphore::new(qtd_medicos)),
-            salas_cirurgia: Arc::new(Semaphore::new(qtd_salas)),
-            leitos: Arc::new(Semaphore::new(qtd_leitos)),
-            equipamentos_exames: Arc::new(Semaphore::new(qtd_exames)), // NOVO: Inicialização do semáforo
+            pools: config.iter().copied().map(PoolRecurso::novo).collect(),
+            historico_uso: Arc::new(HistoricoUso::new(&tipos, controle.clone())),
+            politica_ordem: PoliticaOrdem::default(),
+            posse: Arc::new(Mutex::new(HashMap::new())),
+            violacoes_ordem: Mutex::new(vec![]),
+            preemptados: Mutex::new(HashSet::new()),
+            cancelamentos: Mutex::new(HashMap::new()),
+            preempcoes: AtomicUsize::new(0),
+            ocorrencias_deadlock: Mutex::new(vec![]),
+            preferenciais: Mutex::new(HashSet::new()),
+            banqueiro: false,
+            necessidades: Mutex::new(HashMap::new()),
+            decisao_alocacao: Mutex::new(()),
+            preferencia_alterada: Notify::new(),
+            controle,
+        }
+    }
 
-            // Inicialização dos slots
-            slots_medicos: Arc::new(Mutex::new(vec![None; qtd_medicos])),
-            slots_salas: Arc::new(Mutex::new(vec![None; qtd_salas])),
-            slots_leitos: Arc::new(Mutex::new(vec![None; qtd_leitos])),
-            slots_exames: Arc::new(Mutex::new(vec![None; qtd_exames])), // NOVO: Inicialização dos slots
+    /// Usa um controle de execução compartilhado (ex.: o mesmo para todos os hospitais).
+    /// O histórico de uso passa a registrar os eventos no relógio desse controle.
+    pub fn com_controle(mut self, controle: Arc<ControleSimulacao>) -> Self {
+        let tipos: Vec<TipoRecurso> = self.pools.iter().map(|p| p.config.tipo).collect();
+        self.historico_uso = Arc::new(HistoricoUso::new(&tipos, controle.clone()));
+        self.controle = controle;
+        self
+    }
 
-            fila_medicos: Arc::new(Mutex::new(vec![])),
-            fila_salas: Arc::new(Mutex::new(vec![])),
-            fila_leitos: Arc::new(Mutex::new(vec![])),
-            fila_exames: Arc::new(Mutex::new(vec![])), // NOVO: Inicialização da fila
+    /// Ativa a verificação da ordem global dos recursos.
+    pub fn com_politica_ordem(mut self, politica: PoliticaOrdem) -> Self {
+        self.politica_ordem = politica;
+        self
+    }
 
-            deadlock_medicos: Arc::new(Mutex::new(vec![])),
-            deadlock_salas: Arc::new(Mutex::new(vec![])),
-            deadlock_leitos: Arc::new(Mutex::new(vec![])),
-            deadlock_exames: Arc::new(Mutex::new(vec![])), // NOVO: Inicialização do deadlock
-            historico_uso: Arc::new(HistoricoUso::new()),
-        }
+    /// Ativa a evitação de deadlock pelo algoritmo do banqueiro.
+    pub fn com_algoritmo_banqueiro(mut self) -> Self {
+        self.banqueiro = true;
+        self
     }
 
-    /// Função de reserva que usa o novo Mutex dos slots e garante exclusividade por paciente.
+    /// Busca o pool de um tipo de recurso.
+    pub fn pool(&self, tipo: &str) -> Option<&PoolRecurso> {
+        self.pools.iter().find(|p| p.config.tipo == tipo)
+    }
+
+    /// Função de reserva que usa o Mutex dos slots do pool e garante exclusividade por paciente.
+    /// A `paciencia` permite que o paciente recuse uma fila longa ou a abandone após esperar demais.
     async fn reservar_recurso(
-        fila: &Arc<Mutex<Vec<String>>>,
-        recurso_sem: Arc<Semaphore>,
-        deadlock: &Arc<Mutex<Vec<String>>>,
-        slots: &Arc<Mutex<Vec<Option<String>>>>, 
-        historico_uso: &Arc<HistoricoUso>,
-        historico_eventos: &Mutex<Vec<EventoUso>>,
+        &self,
+        pool: &PoolRecurso,
         nome: String,
+        etapa: &str,
         timeout_alerta_secs: u64,
-    ) -> Result<OwnedSemaphorePermit, String> { // <-- Retorna Result para indicar falha
+        paciencia: Paciencia,
+    ) -> Result<ReservaRecurso, FalhaReserva> { // <-- Retorna Result para indicar falha
+        let PoolRecurso { semaforo: recurso_sem, slots, fila, deadlock, .. } = pool;
         
         // ---------------- GARANTIA DE EXCLUSIVIDADE ----------------
         // Verifica se o paciente já está ocupando algum slot deste tipo de recurso.
@@ -162,138 +639,409 @@ impl Recursos {
             let slots_lock = slots.lock().await;
             if slots_lock.iter().any(|slot| slot.as_ref() == Some(&nome)) {
                 // Se o nome do paciente já estiver em algum slot, a reserva é negada.
-                return Err(format!("Paciente {} já está reservando um recurso deste tipo!", nome));
+                return Err(FalhaReserva::Exclusividade(format!("Paciente {} já está reservando um recurso deste tipo!", nome)));
             }
         } // O lock 'slots_lock' é liberado aqui, antes de qualquer await longo.
         // ---------------- FIM GARANTIA DE EXCLUSIVIDADE ----------------
 
-        let start_time = Instant::now();
+        let start_time = self.controle.agora();
         
+        let preferenciais = self.preferenciais.lock().await;
         let mut f = fila.lock().await; 
         if !f.contains(&nome) {
-            f.push(nome.clone());
+            // Balking: o paciente olha o tamanho da fila antes de entrar.
+            if let Some(limite) = paciencia.fila_max {
+                if f.len() >= limite {
+                    return Err(FalhaReserva::FilaLonga { tamanho: f.len() });
+                }
+            }
+            let lugar = lugar_na_fila(&f, &nome, &preferenciais);
+            f.insert(lugar, nome.clone());
         }
         drop(f);
+        drop(preferenciais);
 
+        let _espera = self.historico_uso.registrar_espera(&nome, pool.config.tipo, etapa);
+        let _lugar = LugarNaVez { pool, nome: &nome };
         loop {
-            let acquire_future = recurso_sem.clone().acquire_owned();
+            // Com a simulação pausada nenhuma reserva é concedida
+            self.controle.aguardar_retomada().await;
+
+            if self.foi_preemptado(&nome).await {
+                fila.lock().await.retain(|n| n != &nome);
+                deadlock.lock().await.retain(|n| n != &nome);
+                return Err(FalhaReserva::Preemptado);
+            }
+
+            let obtido = if self.banqueiro {
+                // Com o banqueiro a concessão só acontece se o estado resultante for seguro
+                self.tentar_seguro(&nome, etapa, &[pool]).await.and_then(|mut reservas| reservas.pop())
+            } else {
+                // Reservas com preferência passam na frente: os demais só tentam se sobrar
+                // instância livre para todas as preferenciais que esperam antes deles.
+                let preferencial = self.preferenciais.lock().await.contains(&nome);
+                let a_frente = pool.a_frente(&nome, preferencial);
+                let permit = if a_frente == 0 && !preferencial {
+                    let acquire_future = timeout(Duration::from_millis(500), recurso_sem.clone().acquire_owned());
+                    tokio::select! {
+                        permit = acquire_future => permit.ok().map(|permit| permit.unwrap())
+                            // Se um preferencial chegou durante a espera, a instância é devolvida para ele
+                            .filter(|_| pool.vez.lock().unwrap().is_empty()),
+                        _ = self.preferencia_alterada.notified() => None,
+                    }
+                } else if recurso_sem.available_permits() > a_frente {
+                    recurso_sem.clone().try_acquire_owned().ok()
+                } else {
+                    None
+                };
+                match permit {
+                    Some(permit) => Some(self.efetivar_reserva(pool, &nome, etapa, permit).await),
+                    None => None,
+                }
+            };
 
-            let attempt = timeout(
-                Duration::from_millis(500), 
-                acquire_future
-            ).await;
+            if let Some(reserva) = obtido {
+                // SUCESSO! (no modo banqueiro a reserva já foi efetivada durante a decisão)
+                return Ok(reserva); // Retorno de sucesso com a ReservaRecurso
+            }
 
-            match attempt {
-                Ok(permit) => {
-                    // SUCESSO!
+            // Reneging: o paciente abandona a fila se esperou além da paciência.
+            if let Some(espera_max) = paciencia.espera_max {
+                let espera = self.controle.decorrido(start_time);
+                if espera >= espera_max {
                     fila.lock().await.retain(|n| n != &nome);
                     deadlock.lock().await.retain(|n| n != &nome);
-                    
-                    // REGISTRA O USO DO SLOT AQUI
-                    historico_uso.registrar_inicio(&nome, slots, historico_eventos).await;
-                    
-                    return Ok(permit.unwrap()); // Retorno de sucesso com o OwnedSemaphorePermit
+                    return Err(FalhaReserva::PacienciaEsgotada { espera });
                 }
-                Err(_) => {
-                    // TIMEOUT: Continua tentando.
-                    if start_time.elapsed() >= Duration::from_secs(timeout_alerta_secs) {
-                        let mut dl = deadlock.lock().await;
-                        if !dl.contains(&nome) {
-                            dl.push(nome.clone()); 
-                        }
-                    }
-                    sleep(Duration::from_millis(100)).await;
+            }
+
+            // TIMEOUT: Continua tentando.
+            if self.controle.decorrido(start_time) >= Duration::from_secs(timeout_alerta_secs) {
+                let mut dl = deadlock.lock().await;
+                if !dl.contains(&nome) {
+                    dl.push(nome.clone()); 
                 }
             }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Tira o paciente das listas de espera do pool, registra o slot e a posse do recurso e
+    /// entrega a `ReservaRecurso` que desfaz esses registros quando for descartada.
+    async fn efetivar_reserva(&self, pool: &PoolRecurso, nome: &str, etapa: &str, permit: OwnedSemaphorePermit) -> ReservaRecurso {
+        // A reserva existe antes de qualquer registro: se o paciente for cancelado no meio
+        // da efetivação, o descarte dela desfaz o que já tiver sido registrado
+        let mut reserva = ReservaRecurso {
+            ocupacao: Ocupacao {
+                tipo: pool.config.tipo,
+                nome: nome.to_string(),
+                slot: None,
+                slots: pool.slots.clone(),
+                capacidade: pool.capacidade.clone(),
+                reducao_pendente: pool.reducao_pendente.clone(),
+                historico: self.historico_uso.clone(),
+                posse: self.posse.clone(),
+            },
+            permit: Some(permit),
+        };
+        pool.fila.lock().await.retain(|n| n != nome);
+        pool.deadlock.lock().await.retain(|n| n != nome);
+
+        self.registrar_posse(nome, pool.config.tipo).await;
+        // REGISTRA O USO DO SLOT AQUI
+        reserva.ocupacao.slot = self.historico_uso.registrar_inicio(nome, etapa, pool).await;
+        reserva
+    }
+
+    /// Efetiva as reservas de vários pools de uma vez (uma permissão por pool, na mesma ordem).
+    async fn efetivar_reservas(&self, pools: &[&PoolRecurso], nome: &str, etapa: &str, permits: Vec<OwnedSemaphorePermit>) -> ReservasConjuntas {
+        let mut reservas = Vec::with_capacity(pools.len());
+        for (pool, permit) in pools.iter().zip(permits) {
+            reservas.push(self.efetivar_reserva(pool, nome, etapa, permit).await);
         }
+        reservas
+    }
+
+    /// Consome a marca de preempção do paciente, se houver.
+    async fn foi_preemptado(&self, nome: &str) -> bool {
+        self.preemptados.lock().await.remove(nome)
     }
     
     
     // ---------------- Lógica de Preempção (para o Monitor usar) ----------------
 
-    /// Remove um paciente das filas de espera e de deadlock. 
-    pub fn preempcao_paciente(&self, nome_paciente: &str) {
+    /// Remove um paciente das filas de espera e de deadlock de todos os recursos, o marca como
+    /// preemptado e cancela o atendimento dele, onde quer que esteja esperando (ver
+    /// `registrar_cancelamento`); sem alça registrada, a reserva em que ele espera falha com
+    /// `FalhaReserva::Preemptado`. `origem` fica registrada na ocorrência e no motivo.
+    pub async fn preempcao_paciente(&self, nome_paciente: &str, origem: &str) {
+        if self.preemptados.lock().await.insert(nome_paciente.to_string()) {
+            self.preempcoes.fetch_add(1, Ordering::SeqCst);
+            self.ocorrencias_deadlock.lock().await.push(OcorrenciaDeadlock::Preempcao {
+                tempo: self.controle.agora().as_secs_f64(),
+                paciente: nome_paciente.to_string(),
+                origem: origem.to_string(),
+            });
+        }
+
         // Limpa Filas e Deadlocks
-        self.fila_medicos.blocking_lock().retain(|n| n != nome_paciente);
-        self.deadlock_medicos.blocking_lock().retain(|n| n != nome_paciente);
-        self.fila_salas.blocking_lock().retain(|n| n != nome_paciente);
-        self.deadlock_salas.blocking_lock().retain(|n| n != nome_paciente);
-        self.fila_leitos.blocking_lock().retain(|n| n != nome_paciente);
-        self.deadlock_leitos.blocking_lock().retain(|n| n != nome_paciente);
-        self.fila_exames.blocking_lock().retain(|n| n != nome_paciente); // NOVO
-        self.deadlock_exames.blocking_lock().retain(|n| n != nome_paciente); // NOVO
+        for pool in &self.pools {
+            pool.fila.lock().await.retain(|n| n != nome_paciente);
+            pool.deadlock.lock().await.retain(|n| n != nome_paciente);
+        }
+
+        if let Some(cancelamento) = self.cancelamentos.lock().await.get(nome_paciente) {
+            cancelamento.cancelar(MotivoCancelamento::Preempcao { origem: origem.to_string() });
+        }
     }
-    
-    // Funções auxiliares de liberação do slot (chamadas pelo código do Paciente após o uso)
-    
-    pub async fn liberar_medico_slot(&self, nome: &str) {
-        self.historico_uso.registrar_fim(nome, &self.slots_medicos, &self.historico_uso.medico).await;
+
+    /// Registra a alça de cancelamento do paciente enquanto ele estiver em atendimento.
+    pub async fn registrar_cancelamento(&self, nome: &str, cancelamento: Cancelamento) {
+        self.cancelamentos.lock().await.insert(nome.to_string(), cancelamento);
     }
 
-    pub async fn liberar_sala_slot(&self, nome: &str) {
-        self.historico_uso.registrar_fim(nome, &self.slots_salas, &self.historico_uso.sala).await;
+    pub async fn remover_cancelamento(&self, nome: &str) {
+        self.cancelamentos.lock().await.remove(nome);
     }
 
-    pub async fn liberar_leito_slot(&self, nome: &str) {
-        self.historico_uso.registrar_fim(nome, &self.slots_leitos, &self.historico_uso.leito).await;
+    /// Registra uma espera circular confirmada pelo monitor.
+    pub async fn registrar_ciclo(&self, pacientes: &[String]) {
+        self.ocorrencias_deadlock.lock().await.push(OcorrenciaDeadlock::Ciclo {
+            tempo: self.controle.agora().as_secs_f64(),
+            pacientes: pacientes.to_vec(),
+        });
     }
 
-    pub async fn liberar_exame_slot(&self, nome: &str) { // NOVO
-        self.historico_uso.registrar_fim(nome, &self.slots_exames, &self.historico_uso.exame).await;
+    /// Tira o paciente das filas e limpa as posses e marcas dele (usado quando o atendimento termina
+    /// ou é interrompido). Slots, usos no histórico e permissões são devolvidos pelas `ReservaRecurso`
+    /// do próprio paciente, ao serem descartadas.
+    pub async fn liberar_todos(&self, nome: &str) {
+        for pool in &self.pools {
+            pool.fila.lock().await.retain(|n| n != nome);
+            pool.deadlock.lock().await.retain(|n| n != nome);
+        }
+        self.posse.lock().await.remove(nome);
+        self.necessidades.lock().await.remove(nome);
+        self.preemptados.lock().await.remove(nome);
+        self.preferenciais.lock().await.remove(nome);
+    }
+
+    /// Dá (ou retira) do paciente a preferência nas filas. Ao ganhá-la ele passa à frente de
+    /// quem não tem preferência nas filas em que já está; reservas simples dele passam a ser
+    /// atendidas antes das demais.
+    pub async fn definir_preferencia(&self, nome: &str, preferencial: bool) {
+        let mut preferenciais = self.preferenciais.lock().await;
+        if !preferencial {
+            if preferenciais.remove(nome) {
+                self.preferencia_alterada.notify_waiters();
+            }
+            return;
+        }
+        if !preferenciais.insert(nome.to_string()) {
+            return;
+        }
+
+        for pool in &self.pools {
+            let mut fila = pool.fila.lock().await;
+            if let Some(i) = fila.iter().position(|n| n == nome) {
+                let paciente = fila.remove(i);
+                let lugar = lugar_na_fila(&fila, nome, &preferenciais);
+                fila.insert(lugar, paciente);
+            }
+        }
+        self.preferencia_alterada.notify_waiters();
     }
 
+    // ---------------- Detecção e Evitação de Deadlock ----------------
 
-    // ---------------- Funções públicas de reserva (Atualizadas) ----------------
+    /// Fotografa disponibilidade, posse e pedidos pendentes de todos os pools.
+    pub async fn retrato_alocacao(&self) -> RetratoAlocacao {
+        let mut retrato = RetratoAlocacao::default();
+        for pool in &self.pools {
+            retrato.disponiveis.insert(pool.config.tipo, pool.semaforo.available_permits());
+            for nome in pool.fila.lock().await.iter() {
+                retrato.pedidos.entry(nome.clone()).or_default().push(pool.config.tipo);
+            }
+        }
+        retrato.alocacao = self.posse.lock().await.clone();
+        retrato
+    }
+
+    /// Declara a necessidade máxima do paciente (uma instância por entrada de `tipos`).
+    pub async fn declarar_necessidades(&self, nome: &str, tipos: Vec<TipoRecurso>) {
+        self.necessidades.lock().await.insert(nome.to_string(), tipos);
+    }
+
+    /// Verifica, sem conceder nada, se entregar `tipos` ao paciente deixa o sistema num estado seguro.
+    async fn concessao_segura(&self, nome: &str, tipos: &[TipoRecurso]) -> bool {
+        let mut retrato = self.retrato_alocacao().await;
+        for tipo in tipos {
+            let disponivel = retrato.disponiveis.entry(tipo).or_default();
+            if *disponivel == 0 {
+                return false;
+            }
+            *disponivel -= 1;
+            retrato.alocacao.entry(nome.to_string()).or_default().push(tipo);
+        }
+        retrato.pedidos.remove(nome);
+
+        let necessidades = self.necessidades.lock().await;
+        deadlock::estado_seguro(&retrato, &necessidades)
+    }
+
+    /// Decisão do banqueiro: concede os `pools` pedidos (tudo ou nada) somente se o estado
+    /// resultante for seguro. A reserva é efetivada antes de liberar a próxima decisão.
+    async fn tentar_seguro(&self, nome: &str, etapa: &str, pools: &[&PoolRecurso]) -> Option<ReservasConjuntas> {
+        let _decisao = self.decisao_alocacao.lock().await;
+        let tipos: Vec<TipoRecurso> = pools.iter().map(|p| p.config.tipo).collect();
+        if !self.concessao_segura(nome, &tipos).await {
+            return None;
+        }
+
+        let permits = Self::tentar_todos(pools)?;
+        Some(self.efetivar_reservas(pools, nome, etapa, permits).await)
+    }
+
+    async fn registrar_posse(&self, nome: &str, tipo: TipoRecurso) {
+        self.posse.lock().await.entry(nome.to_string()).or_default().push(tipo);
+    }
+
+    /// Verifica se pedir os `pools` respeita a ordem global em relação ao que o paciente já mantém.
+    /// Violações são registradas em `violacoes_ordem`; com `PoliticaOrdem::Rejeitar` o pedido é negado.
+    async fn verificar_ordem(&self, nome: &str, etapa: &str, pools: &[&PoolRecurso]) -> Result<(), FalhaReserva> {
+        if self.politica_ordem == PoliticaOrdem::Desligada {
+            return Ok(());
+        }
+
+        let mantidos: Vec<(TipoRecurso, u32)> = match self.posse.lock().await.get(nome) {
+            Some(tipos) => tipos
+                .iter()
+                .filter_map(|tipo| self.pool(tipo).map(|p| (*tipo, p.config.ordem)))
+                .collect(),
+            None => return Ok(()),
+        };
+        let Some(maior_mantido) = mantidos.iter().map(|(_, ordem)| *ordem).max() else {
+            return Ok(());
+        };
+
+        let Some(fora_de_ordem) = pools.iter().find(|p| p.config.ordem < maior_mantido) else {
+            return Ok(());
+        };
+
+        let violacao = ViolacaoOrdem {
+            paciente: nome.to_string(),
+            etapa: etapa.to_string(),
+            solicitado: (fora_de_ordem.config.tipo, fora_de_ordem.config.ordem),
+            mantidos,
+            rejeitada: self.politica_ordem == PoliticaOrdem::Rejeitar,
+        };
+        self.violacoes_ordem.lock().await.push(violacao.clone());
+
+        if violacao.rejeitada {
+            Err(FalhaReserva::ViolacaoOrdem(violacao))
+        } else {
+            Ok(())
+        }
+    }
+
+
+    // ---------------- Funções públicas de reserva ----------------
     
-    pub async fn reservar_medico(&self, nome: String) -> Result<OwnedSemaphorePermit, String> { 
-        Self::reservar_recurso(
-            &self.fila_medicos,
-            self.medicos.clone(),
-            &self.deadlock_medicos,
-            &self.slots_medicos, 
-            &self.historico_uso, 
-            &self.historico_uso.medico, 
-            nome,
-            10,
-        ).await
-    }
-
-    pub async fn reservar_sala(&self, nome: String) -> Result<OwnedSemaphorePermit, String> { 
-        Self::reservar_recurso(
-            &self.fila_salas,
-            self.salas_cirurgia.clone(),
-            &self.deadlock_salas,
-            &self.slots_salas, 
-            &self.historico_uso, 
-            &self.historico_uso.sala, 
-            nome,
-            10,
-        ).await
-    }
-
-    pub async fn reservar_leito(&self, nome: String) -> Result<OwnedSemaphorePermit, String> { 
-        Self::reservar_recurso(
-            &self.fila_leitos,
-            self.leitos.clone(),
-            &self.deadlock_leitos,
-            &self.slots_leitos, 
-            &self.historico_uso, 
-            &self.historico_uso.leito, 
-            nome,
-            10,
-        ).await
-    }
-
-    pub async fn reservar_exame(&self, nome: String) -> Result<OwnedSemaphorePermit, String> { // NOVO
-        Self::reservar_recurso(
-            &self.fila_exames,
-            self.equipamentos_exames.clone(),
-            &self.deadlock_exames,
-            &self.slots_exames, 
-            &self.historico_uso, 
-            &self.historico_uso.exame, 
-            nome,
-            10,
-        ).await
-    }
-}
\ No newline at end of file
+    /// Reserva uma instância do recurso `tipo` para a `etapa` do caminho clínico, esperando o tempo que for preciso.
+    pub async fn reservar(&self, tipo: TipoRecurso, nome: String, etapa: &str) -> Result<ReservaRecurso, FalhaReserva> {
+        self.reservar_com_paciencia(tipo, nome, etapa, Paciencia::default()).await
+    }
+
+    /// Reserva uma instância do recurso `tipo`, permitindo que o paciente desista (ver `Paciencia`).
+    pub async fn reservar_com_paciencia(&self, tipo: TipoRecurso, nome: String, etapa: &str, paciencia: Paciencia) -> Result<ReservaRecurso, FalhaReserva> {
+        let pool = self.pool(tipo).ok_or_else(|| FalhaReserva::RecursoInexistente(tipo.to_string()))?;
+        self.verificar_ordem(&nome, etapa, &[pool]).await?;
+        self.reservar_recurso(pool, nome, etapa, TIMEOUT_ALERTA_SECS, paciencia).await
+    }
+
+    /// Reserva atomicamente (tudo ou nada) uma instância de cada tipo em `tipos`.
+    /// O paciente aguarda nas filas de todos os tipos, mas só ocupa recursos quando todos
+    /// estão livres ao mesmo tempo, eliminando a espera com posse (hold-and-wait).
+    /// Como as tentativas usam `try_acquire`, quem já espera no semáforo tem a preferência.
+    pub async fn reservar_conjunto(&self, tipos: &[TipoRecurso], nome: String, etapa: &str) -> Result<ReservasConjuntas, FalhaReserva> {
+        let mut pools = Vec::with_capacity(tipos.len());
+        for tipo in tipos {
+            pools.push(self.pool(tipo).ok_or_else(|| FalhaReserva::RecursoInexistente(tipo.to_string()))?);
+        }
+        self.verificar_ordem(&nome, etapa, &pools).await?;
+
+        // Mesma garantia de exclusividade da reserva simples, para cada tipo pedido.
+        for pool in &pools {
+            if pool.slots.lock().await.iter().any(|slot| slot.as_ref() == Some(&nome)) {
+                return Err(FalhaReserva::Exclusividade(format!(
+                    "Paciente {} já está reservando um recurso do tipo {}!",
+                    nome, pool.config.tipo
+                )));
+            }
+        }
+
+        let preferenciais = self.preferenciais.lock().await;
+        for pool in &pools {
+            let mut f = pool.fila.lock().await;
+            if !f.contains(&nome) {
+                let lugar = lugar_na_fila(&f, &nome, &preferenciais);
+                f.insert(lugar, nome.clone());
+            }
+        }
+        drop(preferenciais);
+        let _esperas: Vec<EsperaNaFila> = pools
+            .iter()
+            .map(|pool| self.historico_uso.registrar_espera(&nome, pool.config.tipo, etapa))
+            .collect();
+
+        let start_time = self.controle.agora();
+        loop {
+            self.controle.aguardar_retomada().await;
+
+            if self.foi_preemptado(&nome).await {
+                for pool in &pools {
+                    pool.fila.lock().await.retain(|n| n != &nome);
+                    pool.deadlock.lock().await.retain(|n| n != &nome);
+                }
+                return Err(FalhaReserva::Preemptado);
+            }
+
+            let obtido = if self.banqueiro {
+                self.tentar_seguro(&nome, etapa, &pools).await
+            } else {
+                match Self::tentar_todos(&pools) {
+                    Some(permits) => Some(self.efetivar_reservas(&pools, &nome, etapa, permits).await),
+                    None => None,
+                }
+            };
+
+            if let Some(reservas) = obtido {
+                // SUCESSO: todos os recursos concedidos de uma vez
+                return Ok(reservas);
+            }
+
+            // Sinaliza espera longa apenas nos recursos que estão esgotados
+            if self.controle.decorrido(start_time) >= Duration::from_secs(TIMEOUT_ALERTA_SECS) {
+                for pool in &pools {
+                    if pool.semaforo.available_permits() == 0 {
+                        let mut dl = pool.deadlock.lock().await;
+                        if !dl.contains(&nome) {
+                            dl.push(nome.clone());
+                        }
+                    }
+                }
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Tenta obter uma permissão de cada pool sem esperar. Se alguma faltar,
+    /// as permissões já obtidas são devolvidas (descartadas) e nada é concedido.
+    fn tentar_todos(pools: &[&PoolRecurso]) -> Option<Vec<OwnedSemaphorePermit>> {
+        let mut permits = Vec::with_capacity(pools.len());
+        for pool in pools {
+            permits.push(pool.semaforo.clone().try_acquire_owned().ok()?);
+        }
+        Some(permits)
+    }
+}
@@ -0,0 +1,150 @@
+// rede.rs
+use std::sync::Arc;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+use crate::recursos::{Recursos, TipoRecurso};
+use crate::monitor_gui::EstadoRecursosGUI;
+
+// ---------------- Hospitais da Rede ----------------
+
+/// Um hospital da rede: recursos próprios e o estado exibido pela sua GUI.
+pub struct Hospital {
+    pub nome: &'static str,
+    pub recursos: Arc<Recursos>,
+    pub estado: Arc<Mutex<EstadoRecursosGUI>>,
+}
+
+impl Hospital {
+    pub fn novo(nome: &'static str, recursos: Arc<Recursos>, estado: Arc<Mutex<EstadoRecursosGUI>>) -> Self {
+        Self { nome, recursos, estado }
+    }
+}
+
+// ---------------- Regras e Registro de Transferências ----------------
+
+/// Regra de transferência: se o hospital atual não tem `gatilho` livre no momento da etapa,
+/// o paciente é levado ao hospital da rede com mais instâncias livres desse tipo.
+#[derive(Debug, Clone, Copy)]
+pub struct RegraTransferencia {
+    pub gatilho: TipoRecurso,
+    /// Tempo de transporte, em unidades de tempo (multiplicado pela escala).
+    pub transporte: f64,
+}
+
+/// Transferência realizada, com os tempos de saída e chegada (segundos de tempo simulado).
+#[derive(Debug, Clone, Serialize)]
+pub struct Transferencia {
+    pub paciente: String,
+    pub origem: &'static str,
+    pub destino: &'static str,
+    pub motivo: TipoRecurso,
+    pub saida: f64,
+    pub chegada: f64,
+}
+
+// ---------------- REDE HOSPITALAR ----------------
+
+/// Conjunto de hospitais independentes ligados por regras de transferência.
+pub struct RedeHospitalar {
+    pub hospitais: Vec<Hospital>,
+    pub regras: Vec<RegraTransferencia>,
+    pub transferencias: Mutex<Vec<Transferencia>>,
+}
+
+impl RedeHospitalar {
+    pub fn nova(hospitais: Vec<Hospital>) -> Self {
+        Self {
+            hospitais,
+            regras: vec![],
+            transferencias: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn com_regra(mut self, regra: RegraTransferencia) -> Self {
+        self.regras.push(regra);
+        self
+    }
+
+    /// Escolhe o destino de uma transferência antes de uma etapa que usa `tipo`:
+    /// só há transferência se a origem está esgotada e outro hospital tem o recurso livre.
+    pub fn destino_transferencia(&self, origem: usize, tipo: TipoRecurso) -> Option<(usize, RegraTransferencia)> {
+        let regra = self.regras.iter().find(|r| r.gatilho == tipo).copied()?;
+        if self.hospitais[origem].recursos.pool(tipo)?.semaforo.available_permits() > 0 {
+            return None;
+        }
+
+        self.hospitais
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != origem)
+            .filter_map(|(i, h)| h.recursos.pool(tipo).map(|p| (i, p.semaforo.available_permits())))
+            .filter(|(_, livres)| *livres > 0)
+            // Mais instâncias livres primeiro; em caso de empate, o hospital declarado antes
+            .max_by(|(i, a), (j, b)| a.cmp(b).then(j.cmp(i)))
+            .map(|(destino, _)| (destino, regra))
+    }
+
+    /// Aplica as regras de transferência para o paciente. Se houver transferência, registra-a,
+    /// aguarda o transporte e retorna o índice do hospital de destino.
+    pub async fn transferir(&self, paciente: &str, origem: usize, tipo: TipoRecurso, escala_tempo: f64) -> Option<usize> {
+        let (destino, regra) = self.destino_transferencia(origem, tipo)?;
+        let (de, para) = (&self.hospitais[origem], &self.hospitais[destino]);
+
+        {
+            let mut estado = de.estado.lock().await;
+            estado.atualizar_progresso(paciente, 1.0);
+            estado.registrar_log(&format!(
+                "🚑 {} transferido para {} (sem {} livre), transporte de {:.1} unidades",
+                paciente, para.nome, tipo, regra.transporte
+            ));
+        }
+
+        let controle = &de.recursos.controle;
+        let saida = controle.agora().as_secs_f64();
+        controle.dormir(Duration::from_secs_f64(regra.transporte * escala_tempo)).await;
+        let chegada = controle.agora().as_secs_f64();
+
+        para.estado.lock().await.registrar_log(&format!("🚑 {} chegou transferido de {}", paciente, de.nome));
+        self.transferencias.lock().await.push(Transferencia {
+            paciente: paciente.to_string(),
+            origem: de.nome,
+            destino: para.nome,
+            motivo: tipo,
+            saida,
+            chegada,
+        });
+
+        Some(destino)
+    }
+
+    /// Altera a capacidade de um recurso do hospital `hospital` e registra a mudança no log dele.
+    /// Instâncias ocupadas nunca são revogadas: numa redução, saem quando forem devolvidas.
+    /// Retorna a capacidade anterior.
+    pub async fn alterar_capacidade(&self, hospital: usize, recurso: &str, quantidade: usize, origem: &str) -> Result<usize, String> {
+        let h = self.hospitais.get(hospital).ok_or_else(|| format!("hospital {} não existe", hospital))?;
+        let pool = h.recursos.pool(recurso).ok_or_else(|| format!("recurso '{}' não configurado", recurso))?;
+
+        let anterior = pool.capacidade();
+        pool.alterar_capacidade(quantidade).await;
+        let pendente = pool.reducao_pendente();
+        let aviso = if pendente > 0 { format!("; {} em uso a retirar quando devolvidas", pendente) } else { String::new() };
+        h.estado.lock().await.registrar_log(&format!(
+            "🛠️ Capacidade de {} alterada de {} para {} ({}{})",
+            pool.config.nome, anterior, quantidade, origem, aviso
+        ));
+        Ok(anterior)
+    }
+
+    /// Imprime as transferências realizadas (usado no relatório final).
+    pub async fn imprimir_transferencias(&self) {
+        let transferencias = self.transferencias.lock().await;
+        println!("\n🚑 Transferências na rede: {}", transferencias.len());
+        for t in transferencias.iter() {
+            println!(
+                "- {}: {} → {} (sem {}), saída {:.1}s, chegada {:.1}s",
+                t.paciente, t.origem, t.destino, t.motivo, t.saida, t.chegada
+            );
+        }
+    }
+}
@@ -0,0 +1,325 @@
+// relatorio.rs
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+use tokio::sync::Mutex;
+use crate::exportacao::{self, escapar};
+use crate::jornada::uniao;
+use crate::recursos::{EventoEspera, OcorrenciaDeadlock};
+use crate::simulacao::Simulacao;
+use crate::HistoricoRecursos;
+
+/// Estilo do relatório (embutido: o arquivo abre sem servidor nem internet).
+const ESTILO: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 1100px; color: #282828; }
+h1 { border-bottom: 2px solid #2e8b57; padding-bottom: .3em; }
+h2 { margin-top: 2em; color: #2e8b57; }
+table { border-collapse: collapse; margin: .8em 0; }
+th, td { border: 1px solid #ddd; padding: .3em .7em; text-align: left; }
+th { background: #f2f2f2; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.barra { background: #eee; width: 120px; height: .8em; display: inline-block; vertical-align: middle; }
+.barra span { background: #2e8b57; height: 100%; display: block; }
+.vazio { color: #888; font-style: italic; }
+svg { max-width: 100%; height: auto; }
+";
+
+/// Tabela HTML com o texto de cada célula escapado; colunas numéricas ficam alinhadas à direita.
+fn tabela(cabecalho: &[&str], linhas: &[Vec<String>], numericas: &[usize]) -> String {
+    let mut html = String::from("<table>\n<tr>");
+    for coluna in cabecalho {
+        let _ = write!(html, "<th>{}</th>", escapar(coluna));
+    }
+    html.push_str("</tr>\n");
+    for linha in linhas {
+        html.push_str("<tr>");
+        for (i, celula) in linha.iter().enumerate() {
+            let classe = if numericas.contains(&i) { r#" class="num""# } else { "" };
+            let _ = write!(html, "<td{}>{}</td>", classe, escapar(celula));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn vazio(texto: &str) -> String {
+    format!("<p class=\"vazio\">{}</p>\n", escapar(texto))
+}
+
+fn segundos(valor: f64) -> String {
+    format!("{:.1}s", valor)
+}
+
+/// Percentil `p` (0 a 1) de valores já ordenados.
+fn percentil(ordenados: &[f64], p: f64) -> f64 {
+    if ordenados.is_empty() {
+        return 0.0;
+    }
+    ordenados[((ordenados.len() - 1) as f64 * p).round() as usize]
+}
+
+/// Relatório da execução em um único HTML autocontido: parâmetros do cenário, indicadores,
+/// utilização por recurso, distribuição das esperas, linha do tempo, deadlocks e preempções,
+/// transferências e o resumo de cada paciente. Os gráficos são os mesmos SVG da exportação.
+pub async fn gerar_relatorio(simulacao: &Simulacao, historico: &Mutex<HistoricoRecursos>, parametros: &[(String, String)]) -> String {
+    let rede = &simulacao.rede;
+    let estatisticas = &simulacao.estatisticas;
+    let agora = simulacao.controle.agora().as_secs_f64();
+    let mut corpo = String::new();
+
+    // Esperas encerradas e intervalos de uso de cada paciente em toda a rede
+    let mut esperas_por_hospital: Vec<Vec<EventoEspera>> = vec![];
+    let mut intervalos_espera: HashMap<String, Vec<(f64, f64)>> = HashMap::new();
+    let mut intervalos_uso: HashMap<String, Vec<(f64, f64)>> = HashMap::new();
+    for h in &rede.hospitais {
+        let esperas = h.recursos.historico_uso.esperas.lock().unwrap().clone();
+        for espera in &esperas {
+            let fim = if espera.fim == 0.0 { agora } else { espera.fim };
+            intervalos_espera.entry(espera.nome_paciente.clone()).or_default().push((espera.inicio, fim));
+        }
+        esperas_por_hospital.push(esperas);
+        for (_, eventos) in &h.recursos.historico_uso.eventos {
+            for evento in eventos.lock().await.iter() {
+                let fim = if evento.fim == 0.0 { agora } else { evento.fim };
+                intervalos_uso.entry(evento.nome_paciente.clone()).or_default().push((evento.inicio, fim));
+            }
+        }
+    }
+    let duracoes_espera = |esperas: &[EventoEspera]| -> Vec<f64> {
+        let mut duracoes: Vec<f64> = esperas.iter().filter(|e| e.fim > 0.0).map(|e| e.fim - e.inicio).collect();
+        duracoes.sort_by(f64::total_cmp);
+        duracoes
+    };
+
+    // ---- Parâmetros do cenário ----
+    corpo.push_str("<h2>Parâmetros do cenário</h2>\n");
+    let mut linhas: Vec<Vec<String>> = parametros.iter().map(|(nome, valor)| vec![nome.clone(), valor.clone()]).collect();
+    for h in &rede.hospitais {
+        let capacidades: Vec<String> = h.recursos.pools.iter()
+            .map(|p| format!("{} {} (inicial {})", p.config.nome, p.capacidade(), p.config.capacidade))
+            .collect();
+        linhas.push(vec![format!("Recursos de {}", h.nome), capacidades.join(", ")]);
+    }
+    for regra in &rede.regras {
+        linhas.push(vec![
+            format!("Transferência sem {}", regra.gatilho),
+            format!("transporte de {} unidade(s) de tempo", regra.transporte),
+        ]);
+    }
+    corpo.push_str(&tabela(&["Parâmetro", "Valor"], &linhas, &[]));
+
+    // ---- Indicadores ----
+    let atendimentos: Vec<f64> = estatisticas.atendimentos.lock().unwrap().values().map(|d| d.as_secs_f64()).collect();
+    let abortados = estatisticas.abortados.lock().unwrap().clone();
+    let cancelamentos = estatisticas.cancelamentos.lock().unwrap().clone();
+    let desistencias = estatisticas.desistencias.lock().unwrap().clone();
+    let desfechos = estatisticas.desfechos.lock().unwrap().clone();
+    let taxas = estatisticas.taxas_desfecho();
+    let todas_esperas = duracoes_espera(&esperas_por_hospital.concat());
+    let transferencias = rede.transferencias.lock().await.clone();
+    let mut ocorrencias = vec![];
+    for h in &rede.hospitais {
+        ocorrencias.extend(h.recursos.ocorrencias_deadlock.lock().await.iter().map(|o| (h.nome, o.clone())));
+    }
+    let ciclos = ocorrencias.iter().filter(|(_, o)| matches!(o, OcorrenciaDeadlock::Ciclo { .. })).count();
+    let media = |valores: &[f64]| if valores.is_empty() { 0.0 } else { valores.iter().sum::<f64>() / valores.len() as f64 };
+
+    corpo.push_str("<h2>Indicadores</h2>\n");
+    let indicadores = [
+        ("Tempo simulado", segundos(agora)),
+        ("Atendimentos concluídos", atendimentos.len().to_string()),
+        ("Tempo médio de atendimento", segundos(media(&atendimentos))),
+        ("Atendimentos abortados", abortados.len().to_string()),
+        ("Atendimentos cancelados", cancelamentos.len().to_string()),
+        ("Desistências", desistencias.len().to_string()),
+        ("Espera média em fila", segundos(media(&todas_esperas))),
+        ("Espera em fila (P90)", segundos(percentil(&todas_esperas, 0.9))),
+        ("Maior espera em fila", segundos(todas_esperas.last().copied().unwrap_or(0.0))),
+        ("Transferências", transferencias.len().to_string()),
+        ("Ciclos de espera detectados", ciclos.to_string()),
+        ("Preempções", (ocorrencias.len() - ciclos).to_string()),
+        ("Pacientes tratados (com desfecho)", taxas.tratados.to_string()),
+        ("Desfecho bom", format!("{:.0}%", taxas.bom * 100.0)),
+        ("Complicação", format!("{:.0}%", taxas.complicacao * 100.0)),
+        ("Óbito", format!("{:.0}%", taxas.obito * 100.0)),
+        ("Tratados dentro do prazo", format!("{:.0}%", taxas.dentro_do_prazo * 100.0)),
+    ];
+    let linhas: Vec<Vec<String>> = indicadores.iter().map(|(nome, valor)| vec![nome.to_string(), valor.clone()]).collect();
+    corpo.push_str(&tabela(&["Indicador", "Valor"], &linhas, &[1]));
+
+    // ---- Utilização por recurso ----
+    corpo.push_str("<h2>Utilização por recurso</h2>\n");
+    for h in &rede.hospitais {
+        let _ = writeln!(corpo, "<h3>{}</h3>", escapar(h.nome));
+        corpo.push_str("<table>\n<tr><th>Recurso</th><th>Capacidade</th><th>Usos</th><th>Tempo ocupado</th><th>Uso médio</th><th>Utilização</th></tr>\n");
+        for pool in &h.recursos.pools {
+            let Some(eventos) = h.recursos.historico_uso.eventos(pool.config.tipo) else { continue };
+            let duracoes: Vec<f64> = eventos.lock().await.iter()
+                .map(|e| if e.fim == 0.0 { agora } else { e.fim } - e.inicio)
+                .collect();
+            let ocupado = duracoes.iter().fold(0.0, |total, d| total + d);
+            // Sobre a capacidade final (a capacidade pode ter mudado durante a execução)
+            let utilizacao = if agora > 0.0 && pool.capacidade() > 0 {
+                (ocupado / (pool.capacidade() as f64 * agora)).min(1.0)
+            } else {
+                0.0
+            };
+            let _ = writeln!(
+                corpo,
+                r#"<tr><td>{}</td><td class="num">{}</td><td class="num">{}</td><td class="num">{}</td><td class="num">{}</td><td><span class="barra"><span style="width:{:.0}%"></span></span> {:.0}%</td></tr>"#,
+                escapar(pool.config.nome),
+                pool.capacidade(),
+                duracoes.len(),
+                segundos(ocupado),
+                segundos(media(&duracoes)),
+                utilizacao * 100.0,
+                utilizacao * 100.0
+            );
+        }
+        corpo.push_str("</table>\n");
+    }
+
+    // ---- Tempos de espera ----
+    corpo.push_str("<h2>Tempos de espera em fila</h2>\n");
+    let mut linhas = vec![];
+    for (h, esperas) in rede.hospitais.iter().zip(&esperas_por_hospital) {
+        for pool in &h.recursos.pools {
+            let duracoes = duracoes_espera(&esperas.iter().filter(|e| e.tipo == pool.config.tipo).cloned().collect::<Vec<_>>());
+            if duracoes.is_empty() {
+                continue;
+            }
+            linhas.push(vec![
+                h.nome.to_string(),
+                pool.config.nome.to_string(),
+                duracoes.len().to_string(),
+                segundos(media(&duracoes)),
+                segundos(percentil(&duracoes, 0.5)),
+                segundos(percentil(&duracoes, 0.9)),
+                segundos(duracoes.last().copied().unwrap_or(0.0)),
+            ]);
+        }
+    }
+    if linhas.is_empty() {
+        corpo.push_str(&vazio("Nenhuma espera registrada."));
+    } else {
+        corpo.push_str(&tabela(&["Hospital", "Recurso", "Esperas", "Média", "Mediana", "P90", "Máxima"], &linhas, &[2, 3, 4, 5, 6]));
+    }
+    for (h, esperas) in rede.hospitais.iter().zip(&esperas_por_hospital) {
+        if esperas.iter().any(|e| e.fim > 0.0) {
+            let _ = writeln!(corpo, "<h3>{}</h3>", escapar(h.nome));
+            corpo.push_str(&exportacao::grafico_esperas(esperas, &h.recursos).svg());
+        }
+    }
+
+    // ---- Linha do tempo ----
+    corpo.push_str("<h2>Linha do tempo de ocupação</h2>\n");
+    corpo.push_str("<p>Passe o mouse sobre uma barra para ver paciente, etapa e horários.</p>\n");
+    for h in &rede.hospitais {
+        let _ = writeln!(corpo, "<h3>{}</h3>", escapar(h.nome));
+        corpo.push_str(&exportacao::grafico_ocupacao(&h.recursos).await.svg());
+    }
+    if let Some(principal) = rede.hospitais.first() {
+        let _ = writeln!(corpo, "<h3>Recursos em uso - {}</h3>", escapar(principal.nome));
+        corpo.push_str(&exportacao::grafico_uso(&*historico.lock().await, &principal.recursos).svg());
+    }
+
+    // ---- Deadlocks e preempções ----
+    corpo.push_str("<h2>Deadlocks e preempções</h2>\n");
+    if ocorrencias.is_empty() {
+        corpo.push_str(&vazio("Nenhum ciclo de espera nem preempção."));
+    } else {
+        let linhas: Vec<Vec<String>> = ocorrencias.iter().map(|(hospital, ocorrencia)| match ocorrencia {
+            OcorrenciaDeadlock::Ciclo { tempo, pacientes } => {
+                vec![segundos(*tempo), hospital.to_string(), "Espera circular".to_string(), pacientes.join(", "), "monitor de deadlock".to_string()]
+            }
+            OcorrenciaDeadlock::Preempcao { tempo, paciente, origem } => {
+                vec![segundos(*tempo), hospital.to_string(), "Preempção".to_string(), paciente.clone(), origem.clone()]
+            }
+        }).collect();
+        corpo.push_str(&tabela(&["Tempo", "Hospital", "Ocorrência", "Pacientes", "Origem"], &linhas, &[0]));
+    }
+    let mut violacoes = vec![];
+    for h in &rede.hospitais {
+        violacoes.extend(h.recursos.violacoes_ordem.lock().await.iter().map(|v| vec![h.nome.to_string(), v.to_string()]));
+    }
+    if !violacoes.is_empty() {
+        corpo.push_str("<h3>Violações da ordem global</h3>\n");
+        corpo.push_str(&tabela(&["Hospital", "Violação"], &violacoes, &[]));
+    }
+
+    // ---- Transferências ----
+    corpo.push_str("<h2>Transferências</h2>\n");
+    if transferencias.is_empty() {
+        corpo.push_str(&vazio("Nenhuma transferência."));
+    } else {
+        let linhas: Vec<Vec<String>> = transferencias.iter().map(|t| vec![
+            t.paciente.clone(), t.origem.to_string(), t.destino.to_string(), t.motivo.to_string(), segundos(t.saida), segundos(t.chegada),
+        ]).collect();
+        corpo.push_str(&tabela(&["Paciente", "Origem", "Destino", "Sem", "Saída", "Chegada"], &linhas, &[4, 5]));
+    }
+
+    // ---- Pacientes ----
+    corpo.push_str("<h2>Pacientes</h2>\n");
+    let duracoes_atendimento = estatisticas.atendimentos.lock().unwrap().clone();
+    let nomes: BTreeSet<String> = duracoes_atendimento.keys().cloned()
+        .chain(abortados.iter().cloned())
+        .chain(cancelamentos.keys().cloned())
+        .chain(desistencias.keys().cloned())
+        .chain(intervalos_espera.keys().cloned())
+        .chain(intervalos_uso.keys().cloned())
+        .collect();
+    let total_uniao = |intervalos: Option<&Vec<(f64, f64)>>| {
+        uniao(intervalos.into_iter().flatten().copied()).iter().fold(0.0, |total, (inicio, fim)| total + (fim - inicio))
+    };
+    let linhas: Vec<Vec<String>> = nomes.iter().map(|nome| {
+        let resultado = if duracoes_atendimento.contains_key(nome) {
+            "Concluído".to_string()
+        } else if abortados.contains(nome) {
+            "Abortado".to_string()
+        } else if let Some(motivo) = cancelamentos.get(nome) {
+            format!("Cancelado ({})", motivo)
+        } else if let Some(motivo) = desistencias.get(nome) {
+            format!("Desistiu ({})", motivo)
+        } else {
+            "Em atendimento".to_string()
+        };
+        let (desfecho, porta) = match desfechos.get(nome) {
+            Some(registro) => (
+                format!("{} ({})", registro.desfecho, registro.condicao),
+                format!("{:.1} / {:.1}", registro.porta_tratamento, registro.prazo),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        vec![
+            nome.clone(),
+            resultado,
+            duracoes_atendimento.get(nome).map_or("-".to_string(), |d| segundos(d.as_secs_f64())),
+            segundos(total_uniao(intervalos_espera.get(nome))),
+            segundos(total_uniao(intervalos_uso.get(nome))),
+            transferencias.iter().filter(|t| t.paciente == *nome).count().to_string(),
+            desfecho,
+            porta,
+        ]
+    }).collect();
+    if linhas.is_empty() {
+        corpo.push_str(&vazio("Nenhum paciente admitido."));
+    } else {
+        corpo.push_str(&tabela(
+            &["Paciente", "Resultado", "Tempo total", "Em fila", "Em recurso", "Transferências", "Desfecho", "Porta-tratamento / prazo"],
+            &linhas,
+            &[2, 3, 4, 5, 7],
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"pt-BR\">\n<head>\n<meta charset=\"utf-8\">\n<title>Relatório da Simulação Hospitalar</title>\n<style>{}</style>\n</head>\n<body>\n<h1>Relatório da Simulação Hospitalar</h1>\n{}</body>\n</html>\n",
+        ESTILO, corpo
+    )
+}
+
+/// Grava o relatório HTML da execução em `arquivo`.
+pub async fn salvar_relatorio(simulacao: &Simulacao, historico: &Mutex<HistoricoRecursos>, parametros: &[(String, String)], arquivo: &str) -> std::io::Result<()> {
+    std::fs::write(arquivo, gerar_relatorio(simulacao, historico, parametros).await)?;
+    println!("✅ Relatório HTML salvo em: {}", arquivo);
+    Ok(())
+}
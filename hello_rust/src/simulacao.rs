@@ -0,0 +1,279 @@
+// simulacao.rs
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use rand::Rng;
+use rand::seq::IteratorRandom;
+use tokio::runtime::Handle;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use crate::cancelamento::MotivoCancelamento;
+use crate::clinica;
+use crate::controle::ControleSimulacao;
+use crate::estatisticas::Estatisticas;
+use crate::paciente::{Paciente, ResultadoAtendimento, SinaisPaciente};
+use crate::rede::RedeHospitalar;
+use crate::recursos::{TipoRecurso, LEITO};
+use crate::registrar_log;
+
+/// Ação do operador sobre um paciente em atendimento (menu de contexto do `MonitorGUI`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcaoOperador {
+    /// Interrompe o atendimento e devolve tudo o que o paciente mantém.
+    Abortar,
+    /// Troca a prioridade (0 = normal, 1 = crítico); críticos passam à frente nas filas.
+    AlterarPrioridade(u8),
+    /// Alta antecipada do leito que o paciente ocupa.
+    DarAlta,
+    /// Coloca o paciente à frente de quem não tem preferência nas filas em que espera.
+    PassarAFrente,
+}
+
+/// Simulação em execução: a rede de hospitais e tudo o que é preciso para admitir pacientes
+/// a qualquer momento (pacientes iniciais, GUI, API de controle).
+pub struct Simulacao {
+    pub rede: Arc<RedeHospitalar>,
+    pub estatisticas: Arc<Estatisticas>,
+    pub logs: Arc<Mutex<Vec<String>>>,
+    pub controle: Arc<ControleSimulacao>,
+    pub escala_tempo: f64,
+    /// Runtime em que as tarefas dos pacientes rodam (a admissão pode vir da thread da GUI).
+    runtime: Handle,
+    /// Tarefas dos pacientes admitidos que ainda não foram aguardadas.
+    pacientes: StdMutex<Vec<JoinHandle<()>>>,
+    /// Nomes já admitidos (o nome identifica o paciente nos recursos).
+    nomes: StdMutex<HashSet<String>>,
+    /// Sinais dos pacientes ainda em atendimento, para as ações do operador.
+    em_atendimento: Arc<StdMutex<HashMap<String, Arc<SinaisPaciente>>>>,
+    /// Numeração dos pacientes sorteados (`R01-Aleatorio`, ...).
+    sorteados: AtomicUsize,
+}
+
+impl Simulacao {
+    pub fn nova(
+        rede: Arc<RedeHospitalar>,
+        estatisticas: Arc<Estatisticas>,
+        logs: Arc<Mutex<Vec<String>>>,
+        controle: Arc<ControleSimulacao>,
+        escala_tempo: f64,
+        runtime: Handle,
+    ) -> Self {
+        Self {
+            rede,
+            estatisticas,
+            logs,
+            controle,
+            escala_tempo,
+            runtime,
+            pacientes: StdMutex::new(vec![]),
+            nomes: StdMutex::new(HashSet::new()),
+            em_atendimento: Arc::new(StdMutex::new(HashMap::new())),
+            sorteados: AtomicUsize::new(0),
+        }
+    }
+
+    /// Paciente admitido durante a execução (GUI ou API de controle), com o caminho clínico padrão.
+    pub fn paciente_avulso(nome: &str, idade: u32, condicao: &str, cirurgia: bool, prioridade: u8) -> Paciente {
+        Paciente::novo_com_prioridade(nome, idade, condicao, cirurgia, prioridade)
+            .com_aquisicao_cirurgia(crate::AQUISICAO_CIRURGIA)
+    }
+
+    /// Sorteia um paciente avulso com condição do catálogo clínico e nome ainda não usado.
+    pub fn paciente_aleatorio<R: Rng + ?Sized>(&self, rng: &mut R) -> Paciente {
+        let nomes = self.nomes.lock().unwrap();
+        let nome = loop {
+            let numero = self.sorteados.fetch_add(1, Ordering::SeqCst) + 1;
+            let nome = format!("R{:02}-Aleatorio", numero);
+            if !nomes.contains(&nome) {
+                break nome;
+            }
+        };
+        drop(nomes);
+
+        let condicao = clinica::condicoes().choose(rng).unwrap_or("Outra");
+        let critico = rng.random_bool(0.25);
+        Self::paciente_avulso(&nome, rng.random_range(1..=95), condicao, rng.random_bool(0.3), critico as u8)
+    }
+
+    /// Admite um paciente no hospital `hospital` da rede e inicia o seu atendimento numa tarefa própria.
+    pub fn admitir(&self, paciente: Paciente, hospital: usize) -> Result<(), String> {
+        if hospital >= self.rede.hospitais.len() {
+            return Err(format!("hospital {} não existe (a rede tem {})", hospital, self.rede.hospitais.len()));
+        }
+        if !self.nomes.lock().unwrap().insert(paciente.nome.clone()) {
+            return Err(format!("já existe um paciente chamado {}", paciente.nome));
+        }
+
+        let rede = self.rede.clone();
+        let estatisticas = self.estatisticas.clone();
+        let logs = self.logs.clone();
+        let escala_tempo = self.escala_tempo;
+        let controle = self.controle.clone();
+        let em_atendimento = self.em_atendimento.clone();
+        em_atendimento.lock().unwrap().insert(paciente.nome.clone(), paciente.sinais.clone());
+
+        let handle = self.runtime.spawn(async move {
+            // Aparece na hora no progresso do hospital, antes mesmo de chegar à primeira fila
+            {
+                let mut estado = rede.hospitais[hospital].estado.lock().await;
+                estado.atualizar_progresso(&paciente.nome, 0.0);
+                estado.registrar_log(&format!("🆕 {} admitido ({}, {} anos)", paciente.nome, paciente.condicao, paciente.idade));
+            }
+            registrar_log(
+                &logs,
+                &format!("🔹 Paciente {} iniciou o processo de atendimento.", paciente.nome),
+            ).await;
+
+            estatisticas.iniciar_atendimento(&paciente.nome);
+            let inicio = controle.agora();
+
+            // Onde a simulação do paciente acontece (operador, monitor ou prazo podem cancelá-la a qualquer momento)
+            let resultado = paciente.atender_na_rede(rede.clone(), hospital, escala_tempo).await;
+            em_atendimento.lock().unwrap().remove(&paciente.nome);
+
+            estatisticas.finalizar_atendimento(&paciente.nome, controle.decorrido(inicio), &resultado);
+
+            match &resultado {
+                ResultadoAtendimento::Concluido { porta_tratamento } => {
+                    registrar_log(
+                        &logs,
+                        &format!("✅ Concluído atendimento: {}", paciente.nome),
+                    ).await;
+
+                    let registro = clinica::avaliar_desfecho(
+                        &paciente.condicao,
+                        *porta_tratamento,
+                        escala_tempo,
+                        &mut rand::rng(),
+                    );
+                    registrar_log(
+                        &logs,
+                        &format!(
+                            "🩺 Desfecho de {} ({}): {} (porta-tratamento {:.1}, prazo {:.1})",
+                            paciente.nome, registro.condicao, registro.desfecho, registro.porta_tratamento, registro.prazo
+                        ),
+                    ).await;
+                    estatisticas.registrar_desfecho(&paciente.nome, registro);
+                }
+                ResultadoAtendimento::Desistiu(motivo) => {
+                    registrar_log(
+                        &logs,
+                        &format!("⚠️ Paciente {} desistiu: {}", paciente.nome, motivo),
+                    ).await;
+                }
+                ResultadoAtendimento::Cancelado(motivo) => {
+                    registrar_log(
+                        &logs,
+                        &format!("🛑 Atendimento de {} cancelado: {}", paciente.nome, motivo),
+                    ).await;
+                }
+                ResultadoAtendimento::Abortado => {}
+            }
+        });
+
+        self.pacientes.lock().unwrap().push(handle);
+        Ok(())
+    }
+
+    /// Prioridade atual de um paciente em atendimento.
+    pub fn prioridade(&self, nome: &str) -> Option<u8> {
+        self.em_atendimento.lock().unwrap().get(nome).map(|sinais| sinais.prioridade())
+    }
+
+    /// Executa uma ação do operador sobre um paciente em atendimento. A ação roda no runtime da
+    /// simulação e deixa um evento de auditoria no log geral e no do hospital do paciente.
+    pub fn acao_operador(&self, nome: &str, acao: AcaoOperador) -> Result<(), String> {
+        let sinais = self.em_atendimento.lock().unwrap().get(nome).cloned()
+            .ok_or_else(|| format!("{} não está em atendimento", nome))?;
+
+        let (rede, logs, nome) = (self.rede.clone(), self.logs.clone(), nome.to_string());
+        self.runtime.spawn(async move {
+            // Hospitais onde o paciente aparece, apurados antes da ação (um aborto o tira do progresso)
+            let mut hospitais = vec![];
+            for h in &rede.hospitais {
+                if h.estado.lock().await.progresso_pacientes.contains_key(&nome) {
+                    hospitais.push(h);
+                }
+            }
+
+            let evento = match acao {
+                AcaoOperador::Abortar => {
+                    sinais.cancelamento.cancelar(MotivoCancelamento::Operador);
+                    format!("🛂 Operador interrompeu o atendimento de {} (recursos devolvidos)", nome)
+                }
+                AcaoOperador::AlterarPrioridade(prioridade) => {
+                    sinais.definir_prioridade(prioridade);
+                    for h in &rede.hospitais {
+                        h.recursos.definir_preferencia(&nome, sinais.critico()).await;
+                    }
+                    let rotulo = if sinais.critico() { "crítico" } else { "normal" };
+                    format!("🛂 Operador alterou a prioridade de {} para {}", nome, rotulo)
+                }
+                AcaoOperador::DarAlta => {
+                    let mut em_leito = false;
+                    for h in &rede.hospitais {
+                        if let Some(pool) = h.recursos.pool(LEITO) {
+                            em_leito |= pool.slots.lock().await.iter().any(|slot| slot.as_deref() == Some(nome.as_str()));
+                        }
+                    }
+                    if em_leito {
+                        sinais.pedir_alta();
+                        format!("🛂 Operador deu alta antecipada a {}", nome)
+                    } else {
+                        format!("⚠️ Operador pediu alta de {}, mas ele não está em um leito", nome)
+                    }
+                }
+                AcaoOperador::PassarAFrente => {
+                    for h in &rede.hospitais {
+                        h.recursos.definir_preferencia(&nome, true).await;
+                    }
+                    format!("🛂 Operador passou {} à frente nas filas", nome)
+                }
+            };
+
+            registrar_log(&logs, &evento).await;
+            for h in hospitais {
+                h.estado.lock().await.registrar_log(&evento);
+            }
+        });
+        Ok(())
+    }
+
+    /// Altera a capacidade de um recurso a partir da GUI; a mudança roda no runtime da simulação.
+    /// Uma mudança recusada é avisada nos logs, como as ações do operador.
+    pub fn alterar_capacidade(&self, hospital: usize, recurso: TipoRecurso, quantidade: usize) {
+        let (rede, logs) = (self.rede.clone(), self.logs.clone());
+        self.runtime.spawn(async move {
+            if let Err(e) = rede.alterar_capacidade(hospital, recurso, quantidade, "monitor").await {
+                avisar(&rede, &logs, &format!("⚠️ Capacidade não alterada: {}", e)).await;
+            }
+        });
+    }
+
+    /// Avisa o operador da GUI (ex.: ação recusada) no log geral e nos eventos de cada hospital.
+    pub fn avisar_operador(&self, mensagem: String) {
+        let (rede, logs) = (self.rede.clone(), self.logs.clone());
+        self.runtime.spawn(async move { avisar(&rede, &logs, &mensagem).await });
+    }
+
+    /// Aguarda até que todos os pacientes admitidos terminem, inclusive os admitidos durante a espera.
+    pub async fn aguardar_pacientes(&self) {
+        loop {
+            let pendentes = std::mem::take(&mut *self.pacientes.lock().unwrap());
+            if pendentes.is_empty() {
+                break;
+            }
+            for handle in pendentes {
+                let _ = handle.await;
+            }
+        }
+    }
+}
+
+/// Registra `mensagem` no log geral e no log de eventos de todos os hospitais da rede.
+async fn avisar(rede: &RedeHospitalar, logs: &Arc<Mutex<Vec<String>>>, mensagem: &str) {
+    registrar_log(logs, mensagem).await;
+    for h in &rede.hospitais {
+        h.estado.lock().await.registrar_log(mensagem);
+    }
+}
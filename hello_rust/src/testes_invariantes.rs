@@ -0,0 +1,271 @@
+// testes_invariantes.rs
+//
+// Testes baseados em propriedades: o proptest sorteia misturas de pacientes (caminho clínico e
+// trajetos de cenário), capacidades e estratégias, e a simulação roda com o relógio do tokio
+// pausado; parte dos pacientes tem prazo e é cancelada no ponto em que estiver. Uma vigia confere
+// as invariantes de exclusividade (`auditoria::conferir`) a cada passo de tempo simulado e o estado
+// final é conferido quando todos terminam.
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use proptest::prelude::*;
+use crate::auditoria;
+use crate::cenarios::Estrategia;
+use crate::paciente::{Etapa, Paciente, Trajeto};
+use crate::recursos::{ConfigRecurso, Recursos, TipoRecurso};
+use crate::testes_simulacao::Bancada;
+use crate::CONFIG_RECURSOS;
+
+/// Intervalo de tempo simulado entre duas conferências da vigia.
+const PASSO_VIGIA: Duration = Duration::from_millis(10);
+const NOMES_ETAPAS: [&str; 3] = ["Etapa 1", "Etapa 2", "Etapa 3"];
+const MAX_PACIENTES: usize = 8;
+
+// ---------------- Geração das misturas ----------------
+
+/// Paciente sorteado, antes de virar um `Paciente`.
+#[derive(Debug, Clone)]
+enum Perfil {
+    /// Caminho clínico padrão (consulta, exames, cirurgia ou leito), com paciência opcional
+    /// (espera máxima em segundos e fila máxima).
+    Clinico { critico: bool, cirurgia: bool, paciencia: Option<(u64, usize)> },
+    /// Trajeto de cenário; cada etapa tem as máscaras dos tipos pedidos e devolvidos e a duração.
+    Trajeto { chegada: u8, etapas: Vec<(u8, u8, u8)> },
+}
+
+#[derive(Debug, Clone)]
+struct Mistura {
+    estrategia: Estrategia,
+    /// Capacidade de cada recurso de `CONFIG_RECURSOS`, na mesma ordem.
+    capacidades: Vec<usize>,
+    perfis: Vec<Perfil>,
+    /// Prazo de atendimento (segundos) do i-ésimo paciente; o cancelamento cai em qualquer etapa.
+    prazos: Vec<Option<u8>>,
+}
+
+fn estrategia() -> impl Strategy<Value = Estrategia> {
+    prop_oneof![
+        Just(Estrategia::Deteccao),
+        Just(Estrategia::Ordem),
+        Just(Estrategia::Atomica),
+        Just(Estrategia::Banqueiro),
+    ]
+}
+
+fn clinico() -> impl Strategy<Value = Perfil> {
+    (any::<bool>(), any::<bool>(), proptest::option::of((1u64..=30, 1usize..=4)))
+        .prop_map(|(critico, cirurgia, paciencia)| Perfil::Clinico { critico, cirurgia, paciencia })
+}
+
+fn trajeto() -> impl Strategy<Value = Perfil> {
+    let mascara = 0u8..(1 << CONFIG_RECURSOS.len());
+    // Chegadas a partir de 1: um uso encerrado em t=0 teria `fim == 0.0`, a marca de uso em andamento
+    (1u8..=6, proptest::collection::vec((mascara.clone(), mascara, 1u8..=3), 1..=NOMES_ETAPAS.len()))
+        .prop_map(|(chegada, etapas)| Perfil::Trajeto { chegada, etapas })
+}
+
+fn mistura() -> impl Strategy<Value = Mistura> {
+    estrategia().prop_flat_map(|estrategia| {
+        // Como na simulação, o banqueiro só atende trajetos: o caminho clínico não declara
+        // necessidade máxima, e o banqueiro poderia negar para sempre o que os trajetos pedem.
+        let perfil = match estrategia {
+            Estrategia::Banqueiro => trajeto().boxed(),
+            _ => prop_oneof![clinico(), trajeto()].boxed(),
+        };
+        (
+            Just(estrategia),
+            proptest::collection::vec(1usize..=3, CONFIG_RECURSOS.len()),
+            proptest::collection::vec(perfil, 1..=MAX_PACIENTES),
+            proptest::collection::vec(proptest::option::weighted(0.3, 1u8..=60), MAX_PACIENTES),
+        )
+    })
+    .prop_map(|(estrategia, capacidades, perfis, prazos)| Mistura { estrategia, capacidades, perfis, prazos })
+}
+
+/// Tipos de recurso marcados na máscara (bit `i` = i-ésimo recurso de `CONFIG_RECURSOS`).
+fn tipos_da_mascara(mascara: u8) -> impl Iterator<Item = TipoRecurso> {
+    CONFIG_RECURSOS.iter().enumerate().filter(move |(i, _)| mascara & (1 << i) != 0).map(|(_, c)| c.tipo)
+}
+
+impl Mistura {
+    fn config(&self) -> Vec<ConfigRecurso> {
+        CONFIG_RECURSOS
+            .iter()
+            .zip(&self.capacidades)
+            .map(|(config, &capacidade)| ConfigRecurso { capacidade, ..*config })
+            .collect()
+    }
+
+    fn pacientes(&self) -> Vec<Paciente> {
+        let modo = self.estrategia.modo();
+        self.perfis
+            .iter()
+            .enumerate()
+            .map(|(i, perfil)| (i, match perfil {
+                Perfil::Clinico { critico, cirurgia, paciencia } => {
+                    let nome = format!("G{:02}-{}", i + 1, if *critico { "Critico" } else { "Normal" });
+                    let paciente = Paciente::novo(&nome, 40, "Fratura", *cirurgia, u8::from(*critico)).com_aquisicao_cirurgia(modo);
+                    match paciencia {
+                        Some((espera, fila)) => paciente.com_paciencia(Duration::from_secs(*espera), *fila),
+                        None => paciente,
+                    }
+                }
+                Perfil::Trajeto { chegada, etapas } => {
+                    // Só pede o que ainda não mantém e só devolve o que mantém
+                    let mut retidos: Vec<TipoRecurso> = vec![];
+                    let etapas = etapas
+                        .iter()
+                        .zip(NOMES_ETAPAS)
+                        .map(|(&(pedir, devolver, duracao), nome)| {
+                            let adquirir: Vec<TipoRecurso> = tipos_da_mascara(pedir).filter(|t| !retidos.contains(t)).collect();
+                            retidos.extend(&adquirir);
+                            let liberar: Vec<TipoRecurso> = tipos_da_mascara(devolver).filter(|t| retidos.contains(t)).collect();
+                            retidos.retain(|t| !liberar.contains(t));
+                            Etapa::nova(nome, &adquirir, f64::from(duracao)).liberando(&liberar)
+                        })
+                        .collect();
+                    Paciente::novo(&format!("G{:02}-Trajeto", i + 1), 40, "Dor", false, 0)
+                        .com_trajeto(Trajeto::novo(f64::from(*chegada), etapas).com_modo(modo))
+                }
+            }))
+            .map(|(i, paciente)| match self.prazos[i] {
+                Some(prazo) => paciente.com_prazo(Duration::from_secs(u64::from(prazo))),
+                None => paciente,
+            })
+            .collect()
+    }
+}
+
+// ---------------- Vigia ----------------
+
+/// Confere as invariantes a cada `PASSO_VIGIA` e guarda as violações com o instante em que apareceram.
+async fn vigiar(recursos: Arc<Recursos>, encontradas: Arc<StdMutex<Vec<String>>>) {
+    loop {
+        if let Some(violacoes) = auditoria::conferir(&recursos) {
+            encontradas.lock().unwrap().extend(violacoes.iter().map(ToString::to_string));
+        }
+        tokio::time::sleep(PASSO_VIGIA).await;
+    }
+}
+
+/// Roda a mistura com a vigia ligada e devolve as violações, incluindo as do estado final:
+/// todos os slots livres, filas vazias, permissões devolvidas e todo `EventoUso` encerrado.
+fn rodar(mistura: &Mistura) -> Vec<String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .start_paused(true)
+        .build()
+        .expect("runtime de teste");
+
+    runtime.block_on(async {
+        let config = mistura.config();
+        let bancada = Bancada::nova(mistura.estrategia.configurar(Recursos::novo(&config)), &config);
+        let encontradas = Arc::new(StdMutex::new(vec![]));
+        let vigia = tokio::spawn(vigiar(bancada.recursos.clone(), encontradas.clone()));
+
+        bancada.atender(mistura.pacientes()).await;
+        vigia.abort();
+
+        let mut violacoes = std::mem::take(&mut *encontradas.lock().unwrap());
+        for pool in &bancada.recursos.pools {
+            let tipo = pool.config.tipo;
+            if let Some(nome) = pool.slots.lock().await.iter().flatten().next() {
+                violacoes.push(format!("fim: {} ainda ocupa um slot de {}", nome, tipo));
+            }
+            if !pool.fila.lock().await.is_empty() {
+                violacoes.push(format!("fim: a fila de {} não esvaziou", tipo));
+            }
+            if pool.semaforo.available_permits() != pool.capacidade() {
+                violacoes.push(format!("fim: permissões de {} não foram devolvidas", tipo));
+            }
+            if let Some(eventos) = bancada.recursos.historico_uso.eventos(tipo) {
+                for evento in eventos.lock().await.iter().filter(|e| e.fim == 0.0 || e.fim < e.inicio) {
+                    violacoes.push(format!("fim: uso de {} por {} na {} sem fim", tipo, evento.nome_paciente, evento.etapa));
+                }
+            }
+        }
+        violacoes
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn exclusividade_dos_recursos(mistura in mistura()) {
+        let violacoes = rodar(&mistura);
+        prop_assert!(violacoes.is_empty(), "{} violações, primeiras: {:#?}", violacoes.len(), &violacoes[..violacoes.len().min(5)]);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn auditoria_registra_a_violacao_uma_vez_e_pausa() {
+    let config = [ConfigRecurso { capacidade: 2, ..CONFIG_RECURSOS[0] }];
+    let bancada = Bancada::nova(Recursos::novo(&config), &config);
+    let recursos = bancada.recursos.clone();
+    let auditoria = tokio::spawn(auditoria::iniciar_auditoria(
+        recursos.clone(),
+        bancada.estado.clone(),
+        bancada.logs.clone(),
+        auditoria::ReacaoViolacao::Pausar,
+    ));
+    let alertas = || async { bancada.logs.lock().await.iter().filter(|l| l.starts_with("🚨")).cloned().collect::<Vec<_>>() };
+
+    // Slot ocupado sem permissão e sem uso aberto no histórico
+    recursos.pools[0].slots.lock().await[0] = Some("Intruso".to_string());
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let violacoes = auditoria::conferir(&recursos).expect("nada travado");
+    assert_eq!(
+        violacoes.iter().map(|v| &v.invariante).collect::<Vec<_>>(),
+        [
+            &auditoria::Invariante::SlotsSemPermissao { ocupados: 1, permissoes: 0 },
+            &auditoria::Invariante::UsoDescasado { paciente: "Intruso".to_string(), slots: 1, eventos_abertos: 0 },
+        ]
+    );
+    assert_eq!(alertas().await.len(), 2);
+    assert!(recursos.controle.pausado(), "a auditoria deveria ter pausado a simulação");
+
+    // Enquanto continua inconsistente, a violação não é registrada de novo
+    recursos.controle.retomar();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(alertas().await.len(), 2);
+    assert!(!recursos.controle.pausado());
+
+    auditoria.abort();
+}
+
+#[tokio::test(start_paused = true)]
+async fn reserva_e_devolvida_quando_a_tarefa_e_abortada_ou_entra_em_panico() {
+    let config = [ConfigRecurso { capacidade: 1, ..CONFIG_RECURSOS[0] }];
+    let tipo = config[0].tipo;
+    let recursos = Arc::new(Recursos::novo(&config));
+    let pool = &recursos.pools[0];
+    let eventos = recursos.historico_uso.eventos(tipo).expect("histórico do recurso");
+
+    for entra_em_panico in [false, true] {
+        let tarefa = tokio::spawn({
+            let recursos = recursos.clone();
+            async move {
+                let _reserva = recursos.reservar(tipo, "Retido".to_string(), "Consulta").await.expect("instância livre");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                assert!(!entra_em_panico, "atendimento interrompido por pânico");
+                std::future::pending::<()>().await;
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(pool.slots.lock().await[0].as_deref(), Some("Retido"));
+        assert_eq!(pool.semaforo.available_permits(), 0);
+
+        if !entra_em_panico {
+            tarefa.abort();
+        }
+        assert!(tarefa.await.is_err());
+
+        // Sem nenhuma limpeza explícita: a reserva descartada devolveu tudo
+        assert_eq!(pool.slots.lock().await[0], None);
+        assert_eq!(pool.semaforo.available_permits(), 1);
+        assert!(eventos.lock().await.iter().all(|e| e.fim > e.inicio));
+        assert!(recursos.posse.lock().await.is_empty());
+        assert_eq!(auditoria::conferir(&recursos), Some(vec![]));
+    }
+}
@@ -0,0 +1,417 @@
+// testes_simulacao.rs
+//
+// Testes determinísticos da simulação. O relógio do tokio começa pausado e avança sozinho
+// sempre que todas as tarefas estão esperando (`start_paused`), então minutos de tempo
+// simulado rodam em milissegundos; os desfechos clínicos são sorteados com semente fixa.
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use crate::cancelamento::MotivoCancelamento;
+use crate::cenarios::{self, Estrategia};
+use crate::clinica;
+use crate::controle::ControleSimulacao;
+use crate::estatisticas::{Estatisticas, TaxasDesfecho};
+use crate::monitor::iniciar_monitor;
+use crate::monitor_gui::EstadoRecursosGUI;
+use crate::paciente::{Etapa, Paciente, ResultadoAtendimento, Trajeto};
+use crate::rede::{Hospital, RedeHospitalar};
+use crate::recursos::{ConfigRecurso, OcorrenciaDeadlock, PoliticaOrdem, Recursos, ENFERMEIRO, EXAME, LEITO, MEDICO, SALA};
+use crate::{pacientes_padrao, HistoricoRecursos, CONFIG_RECURSOS, ESCALA_TEMPO};
+
+/// Semente dos sorteios de desfecho.
+const SEMENTE: u64 = 2024;
+/// Limite de tempo simulado de um atendimento: passar dele indica que a simulação travou.
+const LIMITE_SIMULADO: Duration = Duration::from_secs(3600);
+
+/// Fim do atendimento de um paciente, no tempo simulado em que aconteceu.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Termino {
+    pub(crate) paciente: String,
+    pub(crate) resultado: ResultadoAtendimento,
+    pub(crate) tempo: f64,
+}
+
+/// Um hospital com o monitor rodando (deadlock, histórico e logs), como na simulação real,
+/// mas sem GUI. Os pacientes chegam a ele por uma rede de um hospital só (sem transferências).
+/// O monitor é interrompido quando a bancada é descartada.
+pub(crate) struct Bancada {
+    rede: Arc<RedeHospitalar>,
+    pub(crate) recursos: Arc<Recursos>,
+    pub(crate) estado: Arc<Mutex<EstadoRecursosGUI>>,
+    pub(crate) logs: Arc<Mutex<Vec<String>>>,
+    pub(crate) estatisticas: Arc<Estatisticas>,
+    monitor: JoinHandle<()>,
+}
+
+impl Bancada {
+    pub(crate) fn nova(recursos: Recursos, config: &[ConfigRecurso]) -> Self {
+        let recursos = Arc::new(recursos.com_controle(Arc::new(ControleSimulacao::novo())));
+        let estado = Arc::new(Mutex::new(EstadoRecursosGUI::new(config)));
+        let logs = Arc::new(Mutex::new(vec![]));
+        let monitor = tokio::spawn(iniciar_monitor(
+            recursos.clone(),
+            estado.clone(),
+            Arc::new(Mutex::new(HistoricoRecursos::new())),
+            logs.clone(),
+        ));
+        let rede = Arc::new(RedeHospitalar::nova(vec![Hospital::novo("Bancada", recursos.clone(), estado.clone())]));
+        Self { rede, recursos, estado, logs, estatisticas: Arc::new(Estatisticas::novo()), monitor }
+    }
+
+    fn padrao() -> Self {
+        Self::nova(Recursos::novo(CONFIG_RECURSOS), CONFIG_RECURSOS)
+    }
+
+    fn cenario(nome: &str, estrategia: Estrategia) -> (Self, Vec<Paciente>) {
+        let cenario = cenarios::montar(nome, estrategia).expect("cenário do catálogo");
+        let recursos = cenario.estrategia.configurar(Recursos::novo(&cenario.recursos));
+        (Self::nova(recursos, &cenario.recursos), cenario.pacientes)
+    }
+
+    /// Atende os pacientes ao mesmo tempo (uma tarefa cada) e devolve os términos na ordem em
+    /// que aconteceram. Estatísticas e desfechos são registrados como em `Simulacao::admitir`.
+    pub(crate) async fn atender(&self, pacientes: Vec<Paciente>) -> Vec<Termino> {
+        let terminos = Arc::new(StdMutex::new(vec![]));
+        let sorteio = Arc::new(StdMutex::new(StdRng::seed_from_u64(SEMENTE)));
+        let tarefas: Vec<JoinHandle<()>> = pacientes
+            .into_iter()
+            .map(|paciente| {
+                let (rede, recursos, estatisticas) = (self.rede.clone(), self.recursos.clone(), self.estatisticas.clone());
+                let (terminos, sorteio) = (terminos.clone(), sorteio.clone());
+                tokio::spawn(async move {
+                    let inicio = recursos.controle.agora();
+                    let resultado = paciente.atender_na_rede(rede, 0, ESCALA_TEMPO).await;
+                    estatisticas.finalizar_atendimento(&paciente.nome, recursos.controle.decorrido(inicio), &resultado);
+                    if let ResultadoAtendimento::Concluido { porta_tratamento } = &resultado {
+                        let registro = clinica::avaliar_desfecho(
+                            &paciente.condicao,
+                            *porta_tratamento,
+                            ESCALA_TEMPO,
+                            &mut *sorteio.lock().unwrap(),
+                        );
+                        estatisticas.registrar_desfecho(&paciente.nome, registro);
+                    }
+                    terminos.lock().unwrap().push(Termino {
+                        paciente: paciente.nome.clone(),
+                        resultado,
+                        tempo: recursos.controle.agora().as_secs_f64(),
+                    });
+                })
+            })
+            .collect();
+
+        for tarefa in tarefas {
+            tokio::time::timeout(LIMITE_SIMULADO, tarefa)
+                .await
+                .expect("a simulação não terminou dentro do limite de tempo simulado")
+                .expect("tarefa do paciente falhou");
+        }
+        let terminos = std::mem::take(&mut *terminos.lock().unwrap());
+        terminos
+    }
+
+    async fn ocorrencias(&self) -> Vec<OcorrenciaDeadlock> {
+        self.recursos.ocorrencias_deadlock.lock().await.clone()
+    }
+}
+
+impl Drop for Bancada {
+    fn drop(&mut self) {
+        self.monitor.abort();
+    }
+}
+
+fn nomes(terminos: &[Termino]) -> Vec<&str> {
+    terminos.iter().map(|t| t.paciente.as_str()).collect()
+}
+
+fn concluidos(terminos: &[Termino]) -> bool {
+    terminos.iter().all(|t| matches!(t.resultado, ResultadoAtendimento::Concluido { .. }))
+}
+
+/// Roda a simulação padrão num hospital e devolve os términos e as taxas de desfecho.
+async fn rodar_padrao() -> (Vec<Termino>, TaxasDesfecho) {
+    let bancada = Bancada::padrao();
+    let terminos = bancada.atender(pacientes_padrao()).await;
+    (terminos, bancada.estatisticas.taxas_desfecho())
+}
+
+#[tokio::test(start_paused = true)]
+async fn simulacao_padrao_e_reprodutivel() {
+    let relogio_real = std::time::Instant::now();
+    let (primeira, taxas) = rodar_padrao().await;
+    let (segunda, taxas_segunda) = rodar_padrao().await;
+
+    assert_eq!(primeira.len(), pacientes_padrao().len());
+    assert_eq!(primeira, segunda, "mesma entrada e mesma semente devem dar a mesma execução");
+    assert_eq!(taxas, taxas_segunda);
+    // Minutos de tempo simulado, sem esperar por eles
+    assert!(primeira.iter().map(|t| t.tempo).fold(0.0, f64::max) > 60.0);
+    assert!(relogio_real.elapsed() < Duration::from_secs(10));
+}
+
+#[tokio::test(start_paused = true)]
+async fn simulacao_padrao_ordem_e_estatisticas() {
+    let bancada = Bancada::padrao();
+    let terminos = bancada.atender(pacientes_padrao()).await;
+
+    assert_eq!(nomes(&terminos), ORDEM_PADRAO);
+    let desistentes: Vec<&str> = terminos
+        .iter()
+        .filter(|t| matches!(t.resultado, ResultadoAtendimento::Desistiu(_)))
+        .map(|t| t.paciente.as_str())
+        .collect();
+    assert_eq!(desistentes, ["P06-Normal", "P08-Normal"]);
+
+    let estatisticas = &bancada.estatisticas;
+    let concluidos = terminos.iter().filter(|t| matches!(t.resultado, ResultadoAtendimento::Concluido { .. })).count();
+    assert_eq!(estatisticas.atendimentos.lock().unwrap().len(), concluidos);
+    assert_eq!(estatisticas.desistencias.lock().unwrap().len(), desistentes.len());
+    assert_eq!(estatisticas.taxas_desfecho().tratados, concluidos);
+
+    // Sem pacientes, nenhum recurso fica retido
+    for pool in &bancada.recursos.pools {
+        assert_eq!(pool.semaforo.available_permits(), pool.capacidade(), "{} não foi devolvido", pool.config.nome);
+    }
+}
+
+/// Ordem de término da simulação padrão num único hospital.
+const ORDEM_PADRAO: [&str; 8] = [
+    "P06-Normal",
+    "P08-Normal",
+    "P02-Normal",
+    "P04-Critico",
+    "P05-Normal",
+    "P01-Critico",
+    "P03-Normal",
+    "P07-Critico",
+];
+
+#[tokio::test(start_paused = true)]
+async fn critico_passa_a_frente_na_fila_do_medico() {
+    let config = [ConfigRecurso { tipo: MEDICO, nome: "Médicos", instancia: "Médico", icone: "👨‍⚕️", capacidade: 1, ordem: 1 }];
+    let bancada = Bancada::nova(Recursos::novo(&config), &config);
+    let consulta = |chegada| Trajeto::novo(chegada, vec![Etapa::nova("Consulta", &[MEDICO], 2.0)]);
+    let pacientes = vec![
+        Paciente::novo("A-Primeiro", 40, "Dor", false, 0).com_trajeto(consulta(0.0)),
+        Paciente::novo("B-Normal", 40, "Dor", false, 0).com_trajeto(consulta(0.5)),
+        Paciente::novo("C-Critico", 40, "Infarto", false, 1).com_trajeto(consulta(1.0)),
+    ];
+
+    let terminos = bancada.atender(pacientes).await;
+
+    assert!(concluidos(&terminos));
+    assert_eq!(nomes(&terminos), ["A-Primeiro", "C-Critico", "B-Normal"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn reserva_rejeitada_pela_ordem_global_tira_o_paciente_da_fila() {
+    // A sala vem antes do médico na ordem global: quem retém o médico para a cirurgia não pode pedi-la
+    let config = [
+        ConfigRecurso { tipo: SALA, nome: "Salas Cirurgia", instancia: "Sala", icone: "🏥", capacidade: 1, ordem: 1 },
+        ConfigRecurso { tipo: ENFERMEIRO, nome: "Enfermeiros", instancia: "Enfermeiro", icone: "👩‍⚕️", capacidade: 1, ordem: 2 },
+        ConfigRecurso { tipo: MEDICO, nome: "Médicos", instancia: "Médico", icone: "👨‍⚕️", capacidade: 1, ordem: 3 },
+        ConfigRecurso { tipo: EXAME, nome: "Exames", instancia: "Exame", icone: "🔬", capacidade: 1, ordem: 4 },
+        ConfigRecurso { tipo: LEITO, nome: "Leitos", instancia: "Leito", icone: "🛏️", capacidade: 1, ordem: 5 },
+    ];
+    let bancada = Bancada::nova(Recursos::novo(&config).com_politica_ordem(PoliticaOrdem::Rejeitar), &config);
+
+    let terminos = bancada.atender(vec![Paciente::novo("P-Cirurgia", 40, "Dor", true, 0)]).await;
+
+    assert_eq!(terminos.iter().map(|t| &t.resultado).collect::<Vec<_>>(), [&ResultadoAtendimento::Abortado]);
+    assert_eq!(bancada.recursos.violacoes_ordem.lock().await.len(), 1);
+    let estado = bancada.estado.lock().await;
+    assert!(estado.recursos.iter().all(|pool| pool.fila.is_empty()), "o paciente ficou numa fila da GUI");
+    assert!(estado.progresso_pacientes.is_empty(), "o paciente continua em atendimento: {:?}", estado.progresso_pacientes);
+    for pool in &bancada.recursos.pools {
+        assert_eq!(pool.semaforo.available_permits(), 1, "{} não foi devolvido", pool.config.nome);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn monitor_detecta_ciclo_e_preempta_uma_vitima() {
+    let (bancada, pacientes) = Bancada::cenario("leito_medico", Estrategia::Deteccao);
+    let terminos = bancada.atender(pacientes).await;
+
+    // O ciclo é entre os dois primeiros; o atendimento da vítima é cancelado e os demais concluem
+    let preempcao = ResultadoAtendimento::Cancelado(MotivoCancelamento::Preempcao { origem: "monitor de deadlock".to_string() });
+    let cancelados: Vec<&str> = terminos
+        .iter()
+        .filter(|t| t.resultado == preempcao)
+        .map(|t| t.paciente.as_str())
+        .collect();
+    assert_eq!(cancelados.len(), 1);
+    assert!(["A-LeitoPrimeiro", "B-MedicoPrimeiro"].contains(&cancelados[0]));
+    assert_eq!(terminos.iter().filter(|t| matches!(t.resultado, ResultadoAtendimento::Concluido { .. })).count(), 2);
+
+    let ocorrencias = bancada.ocorrencias().await;
+    let [OcorrenciaDeadlock::Ciclo { pacientes, .. }, OcorrenciaDeadlock::Preempcao { paciente, origem, .. }] = ocorrencias.as_slice() else {
+        panic!("esperava um ciclo seguido de uma preempção: {:?}", ocorrencias);
+    };
+    assert_eq!(pacientes, &["A-LeitoPrimeiro", "B-MedicoPrimeiro"]);
+    assert_eq!(paciente, cancelados[0]);
+    assert_eq!(origem, "monitor de deadlock");
+
+    // No log, a detecção vem antes do contorno
+    let logs = bancada.logs.lock().await;
+    let ciclo = logs.iter().position(|l| l.starts_with("🔁")).expect("ciclo no log");
+    let contorno = logs.iter().position(|l| l.starts_with("♻️")).expect("contorno no log");
+    assert!(ciclo < contorno);
+}
+
+#[tokio::test(start_paused = true)]
+async fn deteccao_sempre_termina_os_cenarios() {
+    for (nome, _) in cenarios::CATALOGO {
+        let (bancada, pacientes) = Bancada::cenario(nome, Estrategia::Deteccao);
+        let total = pacientes.len();
+        let terminos = bancada.atender(pacientes).await;
+
+        assert_eq!(terminos.len(), total, "{}", nome);
+        assert!(
+            bancada.ocorrencias().await.iter().any(|o| matches!(o, OcorrenciaDeadlock::Preempcao { .. })),
+            "{}: o ciclo deveria ter sido quebrado por preempção",
+            nome
+        );
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn prevencao_e_evitacao_nao_formam_ciclo() {
+    for (nome, _) in cenarios::CATALOGO {
+        for estrategia in [Estrategia::Atomica, Estrategia::Banqueiro] {
+            let (bancada, pacientes) = Bancada::cenario(nome, estrategia);
+            let terminos = bancada.atender(pacientes).await;
+
+            assert!(concluidos(&terminos), "{} ({}): {:?}", nome, estrategia, terminos);
+            assert!(bancada.ocorrencias().await.is_empty(), "{} ({})", nome, estrategia);
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelamento_interrompe_o_paciente_onde_ele_estiver() {
+    let config = [ConfigRecurso { tipo: MEDICO, nome: "Médicos", instancia: "Médico", icone: "👨‍⚕️", capacidade: 1, ordem: 1 }];
+    let bancada = Bancada::nova(Recursos::novo(&config), &config);
+    let consulta = |chegada, duracao| Trajeto::novo(chegada, vec![Etapa::nova("Consulta", &[MEDICO], duracao)]);
+    let pacientes = vec![
+        // Ocupa o médico de t=0 a t=50, mas o prazo acaba em t=20
+        Paciente::novo("A-Prazo", 40, "Dor", false, 0).com_trajeto(consulta(0.0, 10.0)).com_prazo(Duration::from_secs(20)),
+        // Espera na fila desde t=5 até o operador cancelar, em t=10
+        Paciente::novo("B-Espera", 40, "Dor", false, 0).com_trajeto(consulta(1.0, 1.0)),
+        Paciente::novo("C-Normal", 40, "Dor", false, 0).com_trajeto(consulta(1.5, 1.0)),
+    ];
+    let operador = tokio::spawn({
+        let (sinais, recursos) = (pacientes[1].sinais.clone(), bancada.recursos.clone());
+        async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            assert!(recursos.pools[0].fila.lock().await.iter().any(|n| n == "B-Espera"));
+            sinais.cancelamento.cancelar(MotivoCancelamento::Operador);
+            // O primeiro motivo é o que vale
+            assert!(!sinais.cancelamento.cancelar(MotivoCancelamento::PrazoEsgotado(Duration::ZERO)));
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            assert!(!recursos.pools[0].fila.lock().await.iter().any(|n| n == "B-Espera"));
+        }
+    });
+
+    let terminos = bancada.atender(pacientes).await;
+    operador.await.expect("operador");
+
+    assert_eq!(
+        terminos.iter().map(|t| (t.paciente.as_str(), &t.resultado, t.tempo)).take(2).collect::<Vec<_>>(),
+        [
+            ("B-Espera", &ResultadoAtendimento::Cancelado(MotivoCancelamento::Operador), 10.0),
+            ("A-Prazo", &ResultadoAtendimento::Cancelado(MotivoCancelamento::PrazoEsgotado(Duration::from_secs(20))), 20.0),
+        ]
+    );
+    assert_eq!(nomes(&terminos), ["B-Espera", "A-Prazo", "C-Normal"]);
+
+    // O médico de A voltou no instante do cancelamento e passou para C
+    let eventos = bancada.recursos.historico_uso.eventos(MEDICO).expect("histórico do médico").lock().await.clone();
+    let [a, c] = eventos.as_slice() else { panic!("esperava os usos de A e C: {:?}", eventos) };
+    assert_eq!((a.nome_paciente.as_str(), a.inicio, a.fim), ("A-Prazo", 0.0, 20.0));
+    assert_eq!(c.nome_paciente, "C-Normal");
+    assert!((20.0..20.5).contains(&c.inicio), "C só pegou o médico em t={}", c.inicio);
+    let pool = &bancada.recursos.pools[0];
+    assert_eq!(pool.semaforo.available_permits(), 1);
+    assert!(pool.slots.lock().await.iter().all(Option::is_none));
+    assert!(pool.fila.lock().await.is_empty() && pool.deadlock.lock().await.is_empty());
+
+    let cancelamentos = bancada.estatisticas.cancelamentos.lock().unwrap().clone();
+    assert_eq!(cancelamentos.get("B-Espera").map(String::as_str), Some("interrompido pelo operador"));
+    assert_eq!(cancelamentos.get("A-Prazo").map(String::as_str), Some("prazo de 20.0s esgotado"));
+    assert_eq!(bancada.estado.lock().await.logs.iter().filter(|l| l.starts_with("🛑")).count(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn alterar_capacidade_mantem_cada_instancia_no_seu_slot() {
+    let config = [ConfigRecurso { tipo: MEDICO, nome: "Médicos", instancia: "Médico", icone: "👨‍⚕️", capacidade: 3, ordem: 1 }];
+    let bancada = Bancada::nova(Recursos::novo(&config), &config);
+    let (recursos, pool) = (&bancada.recursos, &bancada.recursos.pools[0]);
+    let reservar = |nome: &str| recursos.reservar(MEDICO, nome.to_string(), "Consulta");
+    let slots = || async { pool.slots.lock().await.iter().map(|s| s.clone().unwrap_or_default()).collect::<Vec<_>>() };
+
+    let (p1, p2, p3) = (reservar("P1").await.unwrap(), reservar("P2").await.unwrap(), reservar("P3").await.unwrap());
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    // As três instâncias estão ocupadas: cada uma sai quando for devolvida, e só do fim
+    pool.alterar_capacidade(1).await;
+    p1.devolver().await;
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(slots().await, ["", "P2", "P3"]);
+    p3.devolver().await;
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(slots().await, ["", "P2"]);
+    assert_eq!(pool.reducao_pendente(), 0);
+    p2.devolver().await;
+    assert_eq!(slots().await, [""]);
+
+    let eventos = recursos.historico_uso.eventos(MEDICO).expect("histórico do médico").lock().await.clone();
+    assert_eq!(eventos.iter().map(|e| (e.nome_paciente.as_str(), e.instancia_id)).collect::<Vec<_>>(), [("P1", 1), ("P2", 2), ("P3", 3)]);
+    assert!(eventos.iter().all(|e| e.fim != 0.0));
+
+    // Quem espera e é acordado por um aumento já encontra o slot novo
+    let p4 = reservar("P4").await.unwrap();
+    let p5 = tokio::spawn({
+        let recursos = recursos.clone();
+        async move { recursos.reservar(MEDICO, "P5".to_string(), "Consulta").await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    pool.alterar_capacidade(2).await;
+    let p5 = p5.await.expect("reserva de P5");
+    assert_eq!(slots().await, ["P4", "P5"]);
+    p4.devolver().await;
+    p5.devolver().await;
+    assert_eq!(pool.semaforo.available_permits(), 2);
+}
+
+#[test]
+fn reserva_descartada_fora_do_runtime_espera_os_registros_travados() {
+    let runtime = tokio::runtime::Runtime::new().expect("runtime");
+    let config = [ConfigRecurso { tipo: MEDICO, nome: "Médicos", instancia: "Médico", icone: "👨‍⚕️", capacidade: 1, ordem: 1 }];
+    let recursos = Arc::new(Recursos::novo(&config).com_controle(Arc::new(ControleSimulacao::novo())));
+    let reserva = runtime.block_on(async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        recursos.reservar(MEDICO, "P1".to_string(), "Consulta").await.expect("reserva do médico")
+    });
+
+    // Outra thread segura os slots enquanto a reserva é descartada fora do runtime
+    let slots = recursos.pools[0].slots.clone();
+    let (travado, aguardar_trava) = std::sync::mpsc::channel();
+    let trava = std::thread::spawn(move || {
+        let _slots = slots.blocking_lock();
+        travado.send(()).expect("aviso da trava");
+        std::thread::sleep(Duration::from_millis(50));
+    });
+    aguardar_trava.recv().expect("trava dos slots");
+    drop(reserva);
+    trava.join().expect("thread da trava");
+
+    let pool = &recursos.pools[0];
+    assert_eq!(pool.semaforo.available_permits(), 1);
+    assert!(pool.slots.blocking_lock().iter().all(Option::is_none), "o slot continua ocupado");
+    assert!(recursos.posse.blocking_lock().is_empty(), "a posse continua registrada");
+    let eventos = recursos.historico_uso.eventos(MEDICO).expect("histórico do médico").blocking_lock();
+    assert!(eventos.iter().all(|e| e.fim > e.inicio), "o uso continua aberto: {:?}", eventos);
+}